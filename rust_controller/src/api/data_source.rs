@@ -0,0 +1,99 @@
+use rocket::response::content::{RawJson, RawXml};
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::{RefOr, Responses};
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use rocket_okapi::openapi;
+use rocket_okapi::response::OpenApiResponderInner;
+use serde::Deserialize;
+
+use crate::api::{Coordinator, Error};
+use crate::vmix::data_source::DataSourceRow;
+
+#[derive(Debug, Deserialize, JsonSchema, FromFormField, Default, Clone, Copy)]
+pub enum DataFormat {
+    #[default]
+    Json,
+    Xml,
+}
+
+#[derive(Responder)]
+pub enum DataSource {
+    Json(RawJson<String>),
+    Xml(RawXml<String>),
+}
+
+impl DataSource {
+    fn new(row: DataSourceRow, format: Option<DataFormat>) -> Self {
+        match format.unwrap_or_default() {
+            DataFormat::Json => DataSource::Json(RawJson(row.to_json())),
+            DataFormat::Xml => DataSource::Xml(RawXml(row.to_xml())),
+        }
+    }
+}
+
+impl OpenApiResponderInner for DataSource {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let mut responses = RawJson::<String>::responses(gen)?;
+        let xml = RawXml::<String>::responses(gen)?;
+        // Both are a string with a 200, only the media type differs
+        if let (Some(RefOr::Object(json)), Some(RefOr::Object(xml))) =
+            (responses.responses.get_mut("200"), xml.responses.get("200"))
+        {
+            json.content.extend(xml.content.clone());
+        }
+        Ok(responses)
+    }
+}
+
+/// # Leaderboard data source
+/// The current leaderboard page of the division, or the leaderboard division, as a vMix data source
+#[openapi(tag = "Data source")]
+#[get("/datasource/leaderboard?<division>&<format>")]
+pub async fn leaderboard_data_source(
+    co: Coordinator,
+    division: Option<&str>,
+    format: Option<DataFormat>,
+) -> Result<DataSource, Error> {
+    let funcs = co.lock().await.leaderboard_funcs(division)?;
+    Ok(DataSource::new(DataSourceRow::new(&funcs), format))
+}
+
+/// # Card data source
+/// Every player in the focused card as a vMix data source
+#[openapi(tag = "Data source")]
+#[get("/datasource/card?<format>")]
+pub async fn card_data_source(
+    co: Coordinator,
+    format: Option<DataFormat>,
+) -> Result<DataSource, Error> {
+    let funcs = co.lock().await.card_funcs()?;
+    Ok(DataSource::new(DataSourceRow::new(&funcs), format))
+}
+
+/// # Focused player data source
+/// The focused player as a vMix data source
+#[openapi(tag = "Data source")]
+#[get("/datasource/player?<format>")]
+pub async fn focused_player_data_source(
+    co: Coordinator,
+    format: Option<DataFormat>,
+) -> Result<DataSource, Error> {
+    let funcs = co.lock().await.focused_player_funcs()?;
+    Ok(DataSource::new(DataSourceRow::new(&funcs), format))
+}
+
+/// # Hole info data source
+/// Hole info as a vMix data source. `hole` starts at 1 and defaults to the hole of the focused player.
+/// Holes the layout of the focused player does not have are not found
+#[openapi(tag = "Data source")]
+#[get("/datasource/hole?<hole>&<format>")]
+pub async fn hole_data_source(
+    co: Coordinator,
+    hole: Option<u8>,
+    format: Option<DataFormat>,
+) -> Result<DataSource, Error> {
+    let mut co = co.lock().await;
+    let hole = hole.unwrap_or((co.current_hole() + 1).min(18) as u8);
+    let funcs = co.hole_info(hole)?;
+    Ok(DataSource::new(DataSourceRow::new(&funcs), format))
+}
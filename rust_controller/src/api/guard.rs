@@ -30,7 +30,10 @@ impl<'r> FromRequest<'r> for Coordinator {
             .await
         {
             None => Outcome::Error((Status::FailedDependency, Error::UnloadedDependency)),
-            Some(a) => Outcome::Success(a.clone()),
+            Some(a) => Outcome::Success(Coordinator(
+                a.0.clone(),
+                Some(format!("{} {}", request.method(), request.uri())),
+            )),
         }
     }
 }
@@ -100,8 +103,28 @@ pub enum Error {
     PlayerInCardNotFound(usize),
     #[error("Group not found")]
     GroupNotFound,
+    #[error("At least one score per club has to count, got {0}")]
+    InvalidCountingScores(usize),
     #[error("Round not initialised")]
     RoundNotInitialised,
+    #[error("Page {page} does not exist, the leaderboard only has {pages} pages")]
+    PageNotFound { page: usize, pages: usize },
+    #[error("Invalid page size: {0}")]
+    InvalidPageSize(usize),
+    #[error("No vMix target named \"{0}\"")]
+    VMixTargetNotFound(String),
+    #[error("No vMix target to send graphics to")]
+    NoVMixTargets,
+    #[error("Overlay {0} does not exist, vMix has overlays 1 to 4")]
+    InvalidOverlay(u8),
+    #[error("Overlay {0} needs an input to take in or toggle")]
+    OverlayInputMissing(u8),
+    #[error("No theme named \"{0}\"")]
+    ThemeNotFound(String),
+    #[error("Another division already has the short name \"{0}\"")]
+    DuplicateShortName(String),
+    #[error("\"{0}\" has no vMix input, give it one in the title mapping")]
+    TitleNotMapped(&'static str),
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
@@ -121,10 +144,20 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
             | NotEnoughHoles { .. }
             | GroupNotFound => Err(Status::InternalServerError),
             UnloadedDependency => Err(Status::FailedDependency),
-            CardIndexNotFound(_) | TooManyHoles | InvalidDivision(_) | PlayerInCardNotFound(_) => {
-                Err(Status::BadRequest)
-            }
-            RoundNotInitialised => Err(Status::FailedDependency),
+            CardIndexNotFound(_)
+            | TooManyHoles
+            | InvalidDivision(_)
+            | PlayerInCardNotFound(_)
+            | PageNotFound { .. }
+            | InvalidPageSize(_)
+            | InvalidCountingScores(_)
+            | VMixTargetNotFound(_)
+            | NoVMixTargets
+            | InvalidOverlay(_)
+            | OverlayInputMissing(_)
+            | ThemeNotFound(_)
+            | DuplicateShortName(_) => Err(Status::BadRequest),
+            RoundNotInitialised | TitleNotMapped(_) => Err(Status::FailedDependency),
         }
     }
 }
@@ -1,8 +1,7 @@
 use std::net::IpAddr;
-use std::path::Path;
+use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 
-use rocket::fairing::AdHoc;
 use rocket::fs::FileServer;
 use rocket::log::LogLevel;
 use rocket::tokio::sync::broadcast::channel;
@@ -17,17 +16,21 @@ use tokio::sync::{Mutex, MutexGuard};
 pub use guard::Error;
 use guard::*;
 use mutation::*;
-pub use websocket::channels::{DivisionUpdate, HoleUpdate, PlayerManagerUpdate};
+pub use websocket::channels::{
+    DivisionUpdate, HighlightUpdate, HoleUpdate, LeaderboardCycleUpdate, PlayerManagerUpdate,
+};
 
 pub use crate::api::websocket::channels::GeneralChannel;
 use crate::api::websocket::htmx::division_updater;
 use crate::api::websocket::HoleFinishedAlert;
 use crate::controller::coordinator::{BroadcastType, FlipUpVMixCoordinator};
-use crate::util;
+use crate::{assets, vmix};
 
 mod coordinator_wrapper;
+mod data_source;
 mod guard;
 mod mutation;
+mod overlay;
 mod query;
 mod update_loop;
 mod vmix_calls;
@@ -36,29 +39,39 @@ mod websocket;
 
 use super::controller::coordinator::leaderboard_cycle;
 
+/// The coordinator, and the API call using it if any
 #[derive(Debug, Clone)]
-struct Coordinator(Arc<Mutex<FlipUpVMixCoordinator>>);
+struct Coordinator(Arc<Mutex<FlipUpVMixCoordinator>>, Option<String>);
 
 impl FlipUpVMixCoordinator {
     pub async fn into_coordinator(
-        self,
+        mut self,
         hole_finished_alert: GeneralChannel<HoleFinishedAlert>,
+        cycle_updater: GeneralChannel<LeaderboardCycleUpdate>,
+        highlight_updater: GeneralChannel<HighlightUpdate>,
+        player_updater: GeneralChannel<PlayerManagerUpdate>,
     ) -> Coordinator {
         let next_group = self.next_group.clone();
+        self.set_queue_cause(None);
         let coordinator = Arc::new(Mutex::new(self));
-        let s = Coordinator(coordinator.clone());
+        let s = Coordinator(coordinator.clone(), None);
         if BroadcastType::Live == *s.clone().lock().await.broadcast_type {
             let leaderboard_cycle =
-                leaderboard_cycle::start_leaderboard_cycle(coordinator.clone()).await;
-            tokio::spawn(async move {
-                update_loop::update_loop(
-                    coordinator,
-                    leaderboard_cycle,
-                    hole_finished_alert,
-                    next_group,
-                )
-                .await;
-            });
+                leaderboard_cycle::start_leaderboard_cycle(coordinator.clone(), cycle_updater)
+                    .await;
+            tokio::spawn(
+                vmix::journal::CAUSE.scope("update loop".to_string(), async move {
+                    update_loop::update_loop(
+                        coordinator,
+                        leaderboard_cycle,
+                        hole_finished_alert,
+                        highlight_updater,
+                        player_updater,
+                        next_group,
+                    )
+                    .await;
+                }),
+            );
         }
 
         s
@@ -66,12 +79,38 @@ impl FlipUpVMixCoordinator {
 }
 
 impl Coordinator {
-    async fn lock(&self) -> MutexGuard<FlipUpVMixCoordinator> {
-        self.0.lock().await
+    async fn lock(&self) -> CoordinatorGuard<'_> {
+        let mut coordinator = self.0.lock().await;
+        coordinator.set_queue_cause(self.1.clone());
+        CoordinatorGuard(coordinator)
+    }
+}
+
+/// The locked coordinator. What it queues is put on the API call until it is unlocked
+struct CoordinatorGuard<'a>(MutexGuard<'a, FlipUpVMixCoordinator>);
+
+impl Deref for CoordinatorGuard<'_> {
+    type Target = FlipUpVMixCoordinator;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for CoordinatorGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Drop for CoordinatorGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set_queue_cause(None);
     }
 }
 
 fn get_normal_routes() -> Vec<Route> {
+    use data_source::*;
     use mutation::*;
     use query::*;
     use vmix_calls::*;
@@ -106,11 +145,57 @@ fn get_normal_routes() -> Vec<Route> {
         next_10_lb,
         reset_lb_pos,
         rewind_lb_pos,
+        set_lb_page,
+        set_lb_page_to_player,
+        set_lb_page_size,
+        leaderboard_page,
         next_featured_hole_card,
         update_featured_hole_group,
         rewind_featured_hole_card,
         set_leaderboard_round,
-        set_hole
+        set_hole,
+        leaderboard_cycle_state,
+        set_cycle_interval,
+        set_cycle_order,
+        exclude_cycle_division,
+        include_cycle_division,
+        pause_cycle,
+        resume_cycle,
+        skip_cycle_to_division,
+        get_highlights,
+        add_virtual_division,
+        remove_virtual_division,
+        get_virtual_divisions,
+        club_standings,
+        show_club_standings,
+        round_summary,
+        show_round_summary,
+        hole_history,
+        set_event_name,
+        hole_metadata,
+        vmix_status,
+        resync_vmix,
+        vmix_journal,
+        overlay,
+        transition,
+        media,
+        select_index,
+        set_countdown,
+        auto_hide_rules,
+        set_auto_hide,
+        remove_auto_hide,
+        mark_replay,
+        mark_replay_in,
+        mark_replay_out,
+        replay_events,
+        replay_settings,
+        set_replay_settings,
+        leaderboard_data_source,
+        card_data_source,
+        focused_player_data_source,
+        hole_data_source,
+        themes,
+        set_theme
     ]
 }
 
@@ -121,7 +206,10 @@ fn get_websocket_routes() -> Vec<Route> {
         hole_watcher,
         division_updater,
         leaderboard_round_watcher,
-        hole_finished_alert
+        hole_finished_alert,
+        leaderboard_cycle_watcher,
+        highlight_watcher,
+        overlay::overlay_watcher
     ]
 }
 
@@ -136,7 +224,14 @@ fn get_webpage_routes() -> Vec<Route> {
     openapi_get_routes![focused_players, set_group, load]
 }
 
+fn get_overlay_routes() -> Vec<Route> {
+    routes![overlay::overlay_page]
+}
+
 pub fn launch() -> Rocket<Build> {
+    vmix::title_mapping::load();
+    assets::load(&rocket::Config::figment());
+
     let (group_selection_sender, _) = channel::<websocket::PlayerManagerUpdate>(1024);
     let group_selection_sender = GeneralChannel::from(group_selection_sender);
     let (hole_update_sender, _) = channel::<HoleUpdate>(1024);
@@ -144,6 +239,8 @@ pub fn launch() -> Rocket<Build> {
     let division_sender = GeneralChannel::from(channel::<websocket::DivisionUpdate>(1024).0);
     let round_sender = GeneralChannel::from(channel::<websocket::LeaderboardRoundUpdate>(1024).0);
     let hole_finished_alert = GeneralChannel::from(channel::<websocket::HoleFinishedAlert>(1024).0);
+    let cycle_sender = GeneralChannel::from(channel::<LeaderboardCycleUpdate>(1024).0);
+    let highlight_sender = GeneralChannel::from(channel::<HighlightUpdate>(1024).0);
 
     let conf = {
         #[cfg(windows)]
@@ -166,17 +263,14 @@ pub fn launch() -> Rocket<Build> {
         .manage(division_sender)
         .manage(round_sender)
         .manage(hole_finished_alert)
+        .manage(cycle_sender)
+        .manage(highlight_sender)
         .mount("/", get_normal_routes())
         .mount("/htmx/", get_webpage_routes())
         .mount("/ws", get_websocket_routes())
         .mount("/ws/htmx/", get_websocket_htmx_routes())
-        .mount("/static", {
-            if cfg!(target_os = "windows") {
-                FileServer::from("C:\\livegrafik-flipup\\_conf\\static")
-            } else {
-                FileServer::from("static")
-            }
-        })
+        .mount("/overlay", get_overlay_routes())
+        .mount("/static", FileServer::from(assets::get().static_files()))
         .attach(Template::fairing())
         .register("/", catchers![make_coordinator,])
         .mount(
@@ -196,9 +290,4 @@ pub fn launch() -> Rocket<Build> {
                 ..Default::default()
             }),
         )
-        .attach(AdHoc::on_shutdown("Shutdown Printer", |_| {
-            Box::pin(async move {
-                util::delete_files_in_directory(Path::new("images")).unwrap();
-            })
-        }))
 }
@@ -1,7 +1,10 @@
 use crate::api::guard::CoordinatorLoader;
 use crate::api::websocket::channels::DivisionUpdate;
 use crate::api::websocket::htmx::division_updater;
-use crate::api::websocket::{hole_finished_alert, HoleFinishedAlert, LeaderboardRoundUpdate};
+use crate::api::websocket::{
+    hole_finished_alert, HighlightUpdate, HoleFinishedAlert, LeaderboardCycleUpdate,
+    LeaderboardRoundUpdate,
+};
 use crate::api::{Coordinator, Error, GeneralChannel, PlayerManagerUpdate};
 use crate::dto;
 use crate::dto::{CoordinatorBuilder, HoleSetting};
@@ -16,6 +19,7 @@ use rocket_okapi::openapi;
 use serde_json::json;
 use std::ops::Deref;
 use std::path::Path;
+use std::time::Duration;
 
 #[openapi(tag = "Config")]
 #[post("/player/focused/set/<focused_player>")]
@@ -40,9 +44,21 @@ pub async fn load(
     loader: &State<CoordinatorLoader>,
     builder: Json<CoordinatorBuilder>,
     hole_finished_alert: GeneralChannel<HoleFinishedAlert>,
+    cycle_updater: GeneralChannel<LeaderboardCycleUpdate>,
+    highlight_updater: GeneralChannel<HighlightUpdate>,
+    player_updater: GeneralChannel<PlayerManagerUpdate>,
 ) {
     let coordinator = builder.into_inner().into_coordinator().await.unwrap();
-    *loader.0.lock().await = Some(coordinator.into_coordinator(hole_finished_alert).await);
+    *loader.0.lock().await = Some(
+        coordinator
+            .into_coordinator(
+                hole_finished_alert,
+                cycle_updater,
+                highlight_updater,
+                player_updater,
+            )
+            .await,
+    );
 }
 
 #[openapi(tag = "Config")]
@@ -78,6 +94,34 @@ pub async fn rewind_lb_pos(coordinator: Coordinator) {
     coordinator.lock().await.decrease_leaderboard_skip();
 }
 
+#[openapi(tag = "Leaderboard")]
+#[post("/leaderboard/page/<page>")]
+pub async fn set_lb_page(coordinator: Coordinator, page: usize) -> Result<(), Error> {
+    coordinator.lock().await.set_leaderboard_page(page)
+}
+
+#[openapi(tag = "Leaderboard")]
+#[post("/leaderboard/page/player/<player_id>")]
+pub async fn set_lb_page_to_player(
+    coordinator: Coordinator,
+    player_id: &str,
+    division_updater: GeneralChannel<DivisionUpdate>,
+) -> Result<(), Error> {
+    let mut coordinator = coordinator.lock().await;
+    coordinator.set_leaderboard_page_to_player(player_id)?;
+    division_updater.send_from_coordinator(coordinator.deref());
+    Ok(())
+}
+
+#[openapi(tag = "Leaderboard")]
+#[post("/leaderboard/page-size/<page_size>")]
+pub async fn set_lb_page_size(coordinator: Coordinator, page_size: usize) -> Result<(), Error> {
+    coordinator
+        .lock()
+        .await
+        .set_leaderboard_page_size(page_size)
+}
+
 #[openapi(tag = "Live Update")]
 #[post("/players/focused/set-group")]
 pub async fn set_group_to_focused_player(
@@ -148,6 +192,36 @@ pub async fn next_queue(
     Ok(())
 }
 
+/// # POST Virtual division
+/// Adds a division made up of players from other divisions, or replaces one with the same name.
+/// It can then be picked like any other division
+#[openapi(tag = "Division")]
+#[post("/divisions/virtual", data = "<definition>")]
+pub async fn add_virtual_division(
+    co: Coordinator,
+    definition: Json<dto::VirtualDivision>,
+    channel: GeneralChannel<DivisionUpdate>,
+) -> Result<(), Error> {
+    let mut co = co.lock().await;
+    co.add_virtual_division(definition.into_inner())?;
+    channel.send_from_coordinator(&co);
+    Ok(())
+}
+
+/// # DELETE Virtual division
+#[openapi(tag = "Division")]
+#[delete("/divisions/virtual/<name>")]
+pub async fn remove_virtual_division(
+    co: Coordinator,
+    name: &str,
+    channel: GeneralChannel<DivisionUpdate>,
+) -> Result<(), Error> {
+    let mut co = co.lock().await;
+    co.remove_virtual_division(name)?;
+    channel.send_from_coordinator(&co);
+    Ok(())
+}
+
 #[openapi(tag = "Division")]
 #[post("/div/<division>/set")]
 pub async fn update_division(
@@ -224,6 +298,116 @@ pub async fn set_hole(coordinator: Coordinator, hole: usize) {
     co.make_hole_info(Some(hole - 1));
 }
 
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/interval/<seconds>")]
+pub async fn set_cycle_interval(
+    coordinator: Coordinator,
+    seconds: u64,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) {
+    let mut co = coordinator.lock().await;
+    co.cycle_settings
+        .set_interval(Duration::from_secs(seconds.max(1)));
+    watcher.send_from_coordinator(&co);
+}
+
+/// # Set cycle order
+/// Divisions are shown in the given order, divisions left out keep their order after them
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/order", data = "<divisions>")]
+pub async fn set_cycle_order(
+    coordinator: Coordinator,
+    divisions: Json<Vec<String>>,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) -> Result<(), Error> {
+    let mut co = coordinator.lock().await;
+    let divisions = divisions
+        .into_inner()
+        .into_iter()
+        .map(|division| {
+            co.find_division_by_name(&division)
+                .ok_or(Error::InvalidDivision(division))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    co.cycle_settings.set_order(divisions);
+    watcher.send_from_coordinator(&co);
+    Ok(())
+}
+
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/exclude/<division>")]
+pub async fn exclude_cycle_division(
+    coordinator: Coordinator,
+    division: &str,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) -> Result<(), Error> {
+    let mut co = coordinator.lock().await;
+    let division = co
+        .find_division_by_name(division)
+        .ok_or(Error::InvalidDivision(division.to_string()))?;
+    co.cycle_settings.exclude(division);
+    watcher.send_from_coordinator(&co);
+    Ok(())
+}
+
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/include/<division>")]
+pub async fn include_cycle_division(
+    coordinator: Coordinator,
+    division: &str,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) -> Result<(), Error> {
+    let mut co = coordinator.lock().await;
+    let division = co
+        .find_division_by_name(division)
+        .ok_or(Error::InvalidDivision(division.to_string()))?;
+    co.cycle_settings.include(&division);
+    watcher.send_from_coordinator(&co);
+    Ok(())
+}
+
+/// # Pause cycle
+/// Keeps the currently cycled division on the leaderboard until resumed
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/pause")]
+pub async fn pause_cycle(
+    coordinator: Coordinator,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) {
+    let mut co = coordinator.lock().await;
+    co.cycle_settings.pause();
+    watcher.send_from_coordinator(&co);
+}
+
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/resume")]
+pub async fn resume_cycle(
+    coordinator: Coordinator,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) {
+    let mut co = coordinator.lock().await;
+    co.cycle_settings.resume();
+    watcher.send_from_coordinator(&co);
+}
+
+/// # Skip to division
+/// Shows the division on the cycling leaderboard right away, also while the cycle is paused
+#[openapi(tag = "Leaderboard cycle")]
+#[post("/leaderboard/cycle/skip/<division>")]
+pub async fn skip_cycle_to_division(
+    coordinator: Coordinator,
+    division: &str,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+) -> Result<(), Error> {
+    let mut co = coordinator.lock().await;
+    let division = co
+        .find_division_by_name(division)
+        .ok_or(Error::InvalidDivision(division.to_string()))?;
+    co.cycle_settings.skip_to(division);
+    watcher.send_from_coordinator(&co);
+    Ok(())
+}
+
 #[catch(424)]
 pub fn make_coordinator() -> RawHtml<Template> {
     let ids = std::fs::read_to_string(Path::new("previous_ids.txt"))
@@ -247,3 +431,11 @@ pub fn make_coordinator() -> RawHtml<Template> {
         json!({"event_ids": ids}),
     ))
 }
+
+/// # Name event
+/// Name used for the event on the hole graphic, like "2025 championship"
+#[openapi(tag = "Hole")]
+#[post("/hole/history/<event_id>/name?<name>")]
+pub async fn set_event_name(coordinator: Coordinator, event_id: &str, name: String) {
+    coordinator.lock().await.set_event_name(event_id, name)
+}
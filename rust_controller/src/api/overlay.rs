@@ -0,0 +1,285 @@
+//! Graphics as HTML pages, for a browser source in OBS or vMix instead of GT titles.
+//!
+//! Pages show the same values as the titles, from the data source rows, and are rendered again
+//! over a websocket when the coordinator broadcasts an update. Open e.g. `/overlay/scorebug` to preview one.
+
+use rocket::futures::FutureExt;
+use rocket::http::RawStr;
+use rocket::request::FromParam;
+use rocket::tokio::select;
+use rocket::Shutdown;
+use rocket_dyn_templates::{Metadata, Template};
+use rocket_ws as ws;
+use rocket_ws::Message;
+use serde_json::{json, Map, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+use crate::api::websocket::{
+    DivisionUpdate, LeaderboardCycleUpdate, LeaderboardRoundUpdate, PlayerManagerUpdate,
+};
+use crate::api::{Coordinator, Error, GeneralChannel, HoleUpdate};
+use crate::vmix::data_source::DataSourceRow;
+
+#[derive(Debug, Clone, Copy)]
+pub enum OverlayGraphic {
+    Scorebug,
+    Leaderboard,
+    Compare,
+    HoleInfo,
+}
+
+impl<'a> FromParam<'a> for OverlayGraphic {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        match param {
+            "scorebug" => Ok(Self::Scorebug),
+            "leaderboard" => Ok(Self::Leaderboard),
+            "compare" => Ok(Self::Compare),
+            "hole-info" => Ok(Self::HoleInfo),
+            _ => Err(param),
+        }
+    }
+}
+
+impl OverlayGraphic {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Scorebug => "scorebug",
+            Self::Leaderboard => "leaderboard",
+            Self::Compare => "compare",
+            Self::HoleInfo => "hole-info",
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            Self::Scorebug => "overlay_scorebug",
+            Self::Leaderboard => "overlay_leaderboard",
+            Self::Compare => "overlay_compare",
+            Self::HoleInfo => "overlay_hole_info",
+        }
+    }
+
+    async fn context(&self, co: &Coordinator, division: Option<&str>) -> Result<Value, Error> {
+        let mut co = co.lock().await;
+        let mut context = match self {
+            Self::Scorebug => {
+                let row = DataSourceRow::new(&co.focused_player_funcs()?);
+                json!({"player": players(&row).into_iter().next()})
+            }
+            Self::Leaderboard => {
+                let row = DataSourceRow::new(&co.leaderboard_funcs(division)?);
+                json!({
+                    "division": division.unwrap_or(co.leaderboard_division.name.as_str()),
+                    "title": text(&row, "ts"),
+                    // Colours in the template are hex without `#`, as in the theme
+                    "accent": text(&row, "accent_Fill_Color")
+                        .map(|accent| accent.trim_start_matches('#').to_string()),
+                    "rows": leaderboard_rows(&row),
+                })
+            }
+            Self::Compare => {
+                let row = DataSourceRow::new(&co.card_funcs()?);
+                json!({"players": players(&row)})
+            }
+            Self::HoleInfo => {
+                let hole = (co.current_hole() + 1).min(18) as u8;
+                let row = DataSourceRow::new(&co.hole_info(hole)?);
+                json!({"hole": fields(&row, &HOLE_INFO_FIELDS)})
+            }
+        };
+        context["graphic"] = json!(self.name());
+        let theme = co.themes.active();
+        context["theme"] = json!({"foreground": theme.foreground, "background": theme.background});
+        Ok(context)
+    }
+
+    async fn render(
+        &self,
+        co: &Coordinator,
+        division: Option<&str>,
+        metadata: &Metadata<'_>,
+    ) -> Result<Option<String>, Error> {
+        let context = self.context(co, division).await?;
+        Ok(metadata
+            .render(self.template(), context)
+            .map(|(_, html)| html))
+    }
+}
+
+const HOLE_INFO_FIELDS: [&str; 13] = [
+    "hole",
+    "parnr",
+    "meternr",
+    "feetnr",
+    "avgresult",
+    "difficulty",
+    "elevation",
+    "eventavg",
+    "eventdifficulty",
+    "alltimeavg",
+    "holename",
+    "teedesc",
+    "basketdesc",
+];
+
+/// Value as shown, vMix values are URL encoded where they would break the function
+fn text(row: &DataSourceRow, column: &str) -> Option<String> {
+    row.get(column)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.replace("%2B", "+"))
+}
+
+fn fields(row: &DataSourceRow, columns: &[&str]) -> Map<String, Value> {
+    columns
+        .iter()
+        .filter_map(|column| Some((column.to_string(), json!(text(row, column)?))))
+        .collect()
+}
+
+/// Players by their place in the card, with the scores they show
+fn players(row: &DataSourceRow) -> Vec<Value> {
+    (1..=4)
+        .filter_map(|n| {
+            let name = text(row, &format!("p{n}name"))?;
+            let scores: Vec<Value> = (1..=18)
+                .filter_map(|hole| {
+                    Some(json!({
+                        "hole": hole,
+                        "score": text(row, &format!("p{n}s{hole}"))?,
+                        "color": text(row, &format!("p{n}h{hole}_Fill_Color")),
+                    }))
+                })
+                .collect();
+            Some(json!({
+                "name": name,
+                "surname": text(row, &format!("p{n}surname")),
+                "position": text(row, &format!("p{n}pos")),
+                "total": text(row, &format!("p{n}scoretot")),
+                "round": text(row, &format!("p{n}scorernd")),
+                "throw": text(row, &format!("p{n}throw")),
+                "move": text(row, &format!("p{n}posmove")),
+                "scores": scores,
+            }))
+        })
+        .collect()
+}
+
+fn leaderboard_rows(row: &DataSourceRow) -> Vec<Value> {
+    (1..)
+        .map_while(|n| row.get(&format!("name_{n}")).map(|_| n))
+        .filter_map(|n| {
+            Some(json!({
+                "position": text(row, &format!("pos_{n}")),
+                "name": text(row, &format!("name_{n}"))?,
+                "round": text(row, &format!("rs_{n}")),
+                "total": text(row, &format!("ts_{n}")),
+                "thru": text(row, &format!("thru_{n}")),
+                "move": text(row, &format!("move{n}")),
+                "division": text(row, &format!("div_{n}")),
+            }))
+        })
+        .collect()
+}
+
+/// Page for a browser source, `division` is for the leaderboard
+#[get("/<graphic>?<division>")]
+pub async fn overlay_page(
+    co: Coordinator,
+    graphic: OverlayGraphic,
+    division: Option<&str>,
+    metadata: Metadata<'_>,
+) -> Result<Template, Error> {
+    let content = graphic.render(&co, division, &metadata).await?;
+    let socket = match division {
+        Some(division) => format!(
+            "/ws/overlay/{}?division={}",
+            graphic.name(),
+            RawStr::new(division).percent_encode()
+        ),
+        None => format!("/ws/overlay/{}", graphic.name()),
+    };
+    Ok(Template::render(
+        "overlay",
+        json!({"graphic": graphic.name(), "socket": socket, "content": content}),
+    ))
+}
+
+/// The broadcasts after which a page can show something else
+struct Updates {
+    players: Receiver<PlayerManagerUpdate>,
+    divisions: Receiver<DivisionUpdate>,
+    holes: Receiver<HoleUpdate>,
+    rounds: Receiver<LeaderboardRoundUpdate>,
+    cycle: Receiver<LeaderboardCycleUpdate>,
+}
+
+impl Updates {
+    /// Waits for any update, false once the channels are closed. Missed updates count as one
+    async fn changed(&mut self) -> bool {
+        fn open<T>(update: Result<T, RecvError>) -> bool {
+            !matches!(update, Err(RecvError::Closed))
+        }
+        select! {
+            update = self.players.recv() => open(update),
+            update = self.divisions.recv() => open(update),
+            update = self.holes.recv() => open(update),
+            update = self.rounds.recv() => open(update),
+            update = self.cycle.recv() => open(update),
+        }
+    }
+}
+
+/// Sends the graphic again when an update changes it
+#[allow(clippy::too_many_arguments)]
+#[get("/overlay/<graphic>?<division>")]
+pub async fn overlay_watcher<'r>(
+    ws: ws::WebSocket,
+    co: Coordinator,
+    graphic: OverlayGraphic,
+    division: Option<String>,
+    metadata: Metadata<'r>,
+    shutdown: Shutdown,
+    players: GeneralChannel<PlayerManagerUpdate>,
+    divisions: GeneralChannel<DivisionUpdate>,
+    holes: GeneralChannel<HoleUpdate>,
+    rounds: GeneralChannel<LeaderboardRoundUpdate>,
+    cycle: GeneralChannel<LeaderboardCycleUpdate>,
+) -> ws::Channel<'r> {
+    use rocket::futures::SinkExt;
+
+    let mut updates = Updates {
+        players: players.subscribe(),
+        divisions: divisions.subscribe(),
+        holes: holes.subscribe(),
+        rounds: rounds.subscribe(),
+        cycle: cycle.subscribe(),
+    };
+    ws.channel(move |mut stream| {
+        Box::pin(async move {
+            let mut last = None;
+            loop {
+                select! {
+                    open = updates.changed() => {
+                        if !open {
+                            break;
+                        }
+                        let Ok(Some(html)) = graphic.render(&co, division.as_deref(), &metadata).await else {
+                            continue;
+                        };
+                        if last.as_ref() != Some(&html) {
+                            if stream.send(Message::from(html.clone())).await.is_err() {
+                                break;
+                            }
+                            last = Some(html);
+                        }
+                    },
+                    _ = shutdown.clone().fuse() => break,
+                }
+            }
+            Ok(())
+        })
+    })
+}
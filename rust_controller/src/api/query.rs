@@ -1,5 +1,6 @@
-use crate::api::Coordinator;
+use crate::api::{Coordinator, Error};
 use crate::dto;
+use crate::flipup_vmix_controls::DEFAULT_COUNTING_SCORES;
 
 use itertools::Itertools;
 use rocket::serde::json::Json;
@@ -45,6 +46,18 @@ pub async fn get_divisions(coordinator: Coordinator) -> Json<Vec<String>> {
     coordinator.lock().await.get_div_names().into()
 }
 
+/// # GET Virtual divisions
+#[openapi(tag = "Division")]
+#[get("/divisions/virtual")]
+pub async fn get_virtual_divisions(coordinator: Coordinator) -> Json<Vec<dto::VirtualDivision>> {
+    coordinator
+        .lock()
+        .await
+        .virtual_divisions
+        .definitions()
+        .into()
+}
+
 #[openapi(tag = "Queue System")]
 #[get("/groups")]
 pub async fn get_groups(coordinator: Coordinator) -> Json<Vec<dto::Group>> {
@@ -77,3 +90,124 @@ pub async fn focused_card(coordinator: Coordinator) -> Json<Vec<dto::Player>> {
     let coordinator = coordinator.lock().await;
     coordinator.dto_card().into()
 }
+
+/// # GET Leaderboard page
+/// The page of the big leaderboard that is currently shown, and how many there are
+#[openapi(tag = "Leaderboard")]
+#[get("/leaderboard/page")]
+pub async fn leaderboard_page(coordinator: Coordinator) -> Json<dto::LeaderboardPage> {
+    coordinator.lock().await.leaderboard_page().into()
+}
+
+/// # GET Leaderboard cycle
+/// Interval, order, exclusions and the currently cycled division
+#[openapi(tag = "Leaderboard cycle")]
+#[get("/leaderboard/cycle")]
+pub async fn leaderboard_cycle_state(coordinator: Coordinator) -> Json<dto::LeaderboardCycleState> {
+    coordinator.lock().await.cycle_settings.to_dto().into()
+}
+
+/// # GET Highlights
+/// Aces, eagles, streaks, round lows and lead changes detected so far.
+/// Use `since` with the id after the last seen highlight to only get new ones
+#[openapi(tag = "Highlights")]
+#[get("/highlights?<since>")]
+pub async fn get_highlights(
+    coordinator: Coordinator,
+    since: Option<usize>,
+) -> Json<Vec<dto::Highlight>> {
+    coordinator
+        .lock()
+        .await
+        .highlights(since.unwrap_or_default())
+        .into()
+}
+
+/// # GET Club standings
+/// Clubs of the division ranked by the sum of their best `counting` total scores
+#[openapi(tag = "Clubs")]
+#[get("/clubs/<division>/standings?<counting>")]
+pub async fn club_standings(
+    coordinator: Coordinator,
+    division: &str,
+    counting: Option<usize>,
+) -> Result<Json<Vec<dto::ClubStanding>>, Error> {
+    let standings = coordinator
+        .lock()
+        .await
+        .club_standings(division, counting.unwrap_or(DEFAULT_COUNTING_SCORES))?;
+    Ok(standings.rows().to_vec().into())
+}
+
+/// # GET Round summary
+/// The round in numbers for the division. `round` starts at 1 and defaults to the leaderboard round
+#[openapi(tag = "Round")]
+#[get("/round/summary/<division>?<round>")]
+pub async fn round_summary(
+    coordinator: Coordinator,
+    division: &str,
+    round: Option<usize>,
+) -> Result<Json<dto::RoundSummary>, Error> {
+    let mut coordinator = coordinator.lock().await;
+    let round = match round {
+        Some(round) => round.checked_sub(1).ok_or(Error::RoundNotInitialised)?,
+        None => coordinator.leaderboard_round,
+    };
+    Ok(coordinator.round_summary(division, round)?.to_dto().into())
+}
+
+/// # GET Hole history
+/// Averages of the hole over the event and every stored event on the layout of the focused player.
+/// Uses the leaderboard division if no division is given
+#[openapi(tag = "Hole")]
+#[get("/hole/<hole>/history?<division>")]
+pub async fn hole_history(
+    coordinator: Coordinator,
+    hole: u8,
+    division: Option<&str>,
+) -> Result<Json<dto::HoleHistory>, Error> {
+    Ok(coordinator
+        .lock()
+        .await
+        .hole_history(hole, division)?
+        .into())
+}
+
+/// # GET Hole metadata
+/// Names, descriptions and commentator notes of the hole on the layout of the focused player
+#[openapi(tag = "Hole")]
+#[get("/hole/<hole>/metadata")]
+pub async fn hole_metadata(
+    coordinator: Coordinator,
+    hole: u8,
+) -> Result<Json<dto::HoleMetadata>, Error> {
+    Ok(coordinator.lock().await.hole_metadata(hole)?.into())
+}
+
+/// # GET vMix connections
+/// Whether the coordinator is connected to every vMix target, and their last connection error
+#[openapi(tag = "VMix")]
+#[get("/vmix/status")]
+pub async fn vmix_status(coordinator: Coordinator) -> Json<Vec<dto::VMixConnection>> {
+    coordinator.lock().await.vmix_queue.status().into()
+}
+
+/// # GET vMix journal
+/// The newest commands made for vMix, oldest first, with the API call or loop that made them.
+/// Older commands are in `vmix_journal.jsonl`
+#[openapi(tag = "VMix")]
+#[get("/vmix/journal?<limit>")]
+pub async fn vmix_journal(
+    coordinator: Coordinator,
+    limit: Option<usize>,
+) -> Json<Vec<dto::JournalEntry>> {
+    coordinator.lock().await.vmix_queue.journal(limit).into()
+}
+
+/// # GET themes
+/// Every colour theme, and which one is in use
+#[openapi(tag = "Theme")]
+#[get("/themes")]
+pub async fn themes(coordinator: Coordinator) -> Json<dto::Themes> {
+    coordinator.lock().await.themes.to_dto().into()
+}
@@ -1,6 +1,7 @@
-use crate::api::websocket::HoleFinishedAlert;
+use crate::api::websocket::{HighlightUpdate, HoleFinishedAlert, PlayerManagerUpdate};
 use crate::api::GeneralChannel;
 use crate::controller;
+use crate::controller::coordinator::highlights::ScoreChange;
 use crate::controller::coordinator::leaderboard_cycle::LeaderboardCycle;
 use crate::controller::coordinator::FlipUpVMixCoordinator;
 use crate::controller::queries::results_getter::PlayerResults;
@@ -38,8 +39,7 @@ impl TjingResultMap {
         &mut self,
         player_id: &str,
         hole_result: crate::controller::queries::results_getter::HoleResult,
-    ) -> bool {
-        let mut needs_update = false;
+    ) -> Option<ScoreChange> {
         let results = self.results.entry(player_id.to_string()).or_default();
 
         if let Some(res) = results
@@ -47,16 +47,18 @@ impl TjingResultMap {
             .flatten()
             .find(|hole| hole.hole_number as u8 == hole_result.hole_number as u8)
         {
-            if res.score != hole_result.score {
-                res.score = hole_result.score;
-                needs_update = true;
+            if res.score == hole_result.score {
+                return None;
             }
+            res.score = hole_result.score;
         } else {
-            results.push(Some(hole_result));
-            needs_update = true
+            results.push(Some(hole_result.clone()));
         }
 
-        needs_update
+        Some(ScoreChange {
+            player_id: player_id.to_string(),
+            result: hole_result,
+        })
     }
 
     #[inline(always)]
@@ -64,25 +66,23 @@ impl TjingResultMap {
         &mut self,
         player_id: &str,
         hole_results: Vec<crate::controller::queries::results_getter::HoleResult>,
-    ) -> bool {
-        let mut needs_update = false;
-        for hole_result in hole_results {
-            if self.update(player_id, hole_result) {
-                needs_update = true;
-            }
-        }
-        needs_update
+    ) -> Vec<ScoreChange> {
+        hole_results
+            .into_iter()
+            .filter_map(|hole_result| self.update(player_id, hole_result))
+            .collect()
     }
 
+    /// Returns every hole result that is new or changed since the last update
     #[inline(always)]
-    pub fn update_all_players(&mut self, players: PlayerResults) -> bool {
-        let mut needs_update = false;
-        for (player_id, hole_results) in players.0.into_iter() {
-            if self.update_many(&player_id.into_inner(), hole_results) {
-                needs_update = true;
-            }
-        }
-        needs_update
+    pub fn update_all_players(&mut self, players: PlayerResults) -> Vec<ScoreChange> {
+        players
+            .0
+            .into_iter()
+            .flat_map(|(player_id, hole_results)| {
+                self.update_many(&player_id.into_inner(), hole_results)
+            })
+            .collect()
     }
 
     pub fn update_mut_player(&self, player: &mut controller::Player) {
@@ -104,9 +104,12 @@ pub async fn update_loop(
     coordinator: Arc<Mutex<FlipUpVMixCoordinator>>,
     leaderboard_cycle: Arc<Mutex<LeaderboardCycle>>,
     hole_finished_alert: GeneralChannel<HoleFinishedAlert>,
+    highlight_updater: GeneralChannel<HighlightUpdate>,
+    player_updater: GeneralChannel<PlayerManagerUpdate>,
     next_group: Arc<Mutex<String>>,
 ) {
-    let temp_coordinator = coordinator.lock().await;
+    let mut temp_coordinator = coordinator.lock().await;
+    temp_coordinator.seed_highlights();
     let mut tjing_result_map = TjingResultMap::new(temp_coordinator.available_players());
 
     let divisions = temp_coordinator.all_divs.clone();
@@ -123,7 +126,8 @@ pub async fn update_loop(
                 warn!("Failed to get results for round {}", round_id);
                 continue;
             };
-            if tjing_result_map.update_all_players(results) {
+            let changes = tjing_result_map.update_all_players(results);
+            if !changes.is_empty() {
                 let mut coordinator = coordinator.lock().await;
                 coordinator
                     .available_players_mut()
@@ -132,6 +136,13 @@ pub async fn update_loop(
                 let div = coordinator.focused_player().division.clone();
                 let queue = coordinator.vmix_queue.clone();
                 coordinator.add_state_to_leaderboard();
+                coordinator.record_hole_history();
+                player_updater.send_from_coordinator(&coordinator);
+                let highlights = coordinator.detect_highlights(&changes);
+                if !highlights.is_empty() {
+                    coordinator.mark_replays(&highlights);
+                    highlight_updater.send(HighlightUpdate::new(highlights));
+                }
                 //coordinator.leaderboard.update_little_lb(&div, queue);
                 if let Some(player) = coordinator
                     .available_players()
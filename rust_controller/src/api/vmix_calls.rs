@@ -1,8 +1,16 @@
+use std::collections::HashMap;
+
+use rocket::serde::json::Json;
 use rocket::State;
 use rocket_okapi::openapi;
 
 use crate::api::{Coordinator, Error, GeneralChannel, HoleUpdate};
-use crate::vmix::functions::{VMixFunction, VMixInterfacer, VMixPlayerInfo};
+use crate::dto;
+use crate::dto::AutoHideAction;
+use crate::flipup_vmix_controls::DEFAULT_COUNTING_SCORES;
+use crate::vmix::functions::{
+    InputSelection, MediaAction, OverlayAction, Transition, VMixInterfacer,
+};
 
 /// # Play animation
 /// Play the animation that corresponds with the upcoming score of the currently focused player
@@ -33,6 +41,9 @@ pub async fn increase_score(
 ) -> Result<(), Error> {
     let mut coordinator = co.lock().await;
     coordinator.increase_score(hole_update)?;
+    coordinator
+        .auto_hide
+        .trigger(AutoHideAction::IncreaseScore, &coordinator.vmix_queue);
     Ok(())
 }
 
@@ -73,7 +84,10 @@ pub async fn play_ob_animation(co: Coordinator) -> Result<(), Error> {
 #[openapi(tag = "VMix")]
 #[post("/vmix/hole-info/set")]
 pub async fn set_hole_info(co: Coordinator) {
-    co.lock().await.make_hole_info(None)
+    let mut co = co.lock().await;
+    co.make_hole_info(None);
+    co.auto_hide
+        .trigger(AutoHideAction::SetHoleInfo, &co.vmix_queue);
 }
 
 /// # Update other leaderboard
@@ -89,3 +103,223 @@ pub async fn update_other_leaderboard(co: Coordinator, division: &str) -> Result
     //coordinator.make_separate_lb(&division);
     Ok(())
 }
+
+/// # Show club standings
+/// Set the club standings title to the clubs of the division, ranked by their best `counting` scores
+#[openapi(tag = "VMix")]
+#[post("/vmix/clubs/<division>/standings?<counting>")]
+pub async fn show_club_standings(
+    co: Coordinator,
+    division: &str,
+    counting: Option<usize>,
+) -> Result<(), Error> {
+    let mut co = co.lock().await;
+    co.show_club_standings(division, counting.unwrap_or(DEFAULT_COUNTING_SCORES))?;
+    co.auto_hide
+        .trigger(AutoHideAction::ClubStandings, &co.vmix_queue);
+    Ok(())
+}
+
+/// # Show round summary
+/// Set the round in numbers title for the division. `round` starts at 1 and defaults to the leaderboard round
+#[openapi(tag = "VMix")]
+#[post("/vmix/round/summary/<division>?<round>")]
+pub async fn show_round_summary(
+    co: Coordinator,
+    division: &str,
+    round: Option<usize>,
+) -> Result<(), Error> {
+    let mut co = co.lock().await;
+    let round = match round {
+        Some(round) => round.checked_sub(1).ok_or(Error::RoundNotInitialised)?,
+        None => co.leaderboard_round,
+    };
+    co.show_round_summary(division, round)?;
+    co.auto_hide
+        .trigger(AutoHideAction::RoundSummary, &co.vmix_queue);
+    Ok(())
+}
+
+/// # Resync vMix
+/// Sends every field again, even the ones vMix should already show. For when vMix shows something else.
+/// Resyncs every vMix target if no target is given
+#[openapi(tag = "VMix")]
+#[post("/vmix/resync?<target>")]
+pub async fn resync_vmix(co: Coordinator, target: Option<&str>) -> Result<(), Error> {
+    co.lock().await.vmix_queue.resync(target)
+}
+
+/// # Overlay
+/// Take an input in or out of overlay channel `number`, 1 to 4. Out and off take whatever is on the channel off.
+/// The input can be a GUID, key, number or name, or a title in the title mapping
+#[openapi(tag = "VMix")]
+#[post("/vmix/overlay/<number>?<action>&<input>")]
+pub async fn overlay(
+    co: Coordinator,
+    number: u8,
+    action: OverlayAction,
+    input: Option<String>,
+) -> Result<(), Error> {
+    if !(1..=4).contains(&number) {
+        return Err(Error::InvalidOverlay(number));
+    }
+    if input.is_none() && matches!(action, OverlayAction::In | OverlayAction::Toggle) {
+        return Err(Error::OverlayInputMissing(number));
+    }
+    let function = VMixInterfacer::overlay(number, action, input.map(InputSelection::input));
+    let co = co.lock().await;
+    // Taken by hand, so an earlier action should not take it out
+    co.auto_hide.cancel(number);
+    co.vmix_queue.add(std::iter::once(function));
+    Ok(())
+}
+
+/// # Transition
+/// Put the input on the program output. `duration` is in milliseconds, and ignored by cut
+#[openapi(tag = "VMix")]
+#[post("/vmix/transition/<input>?<kind>&<duration>")]
+pub async fn transition(co: Coordinator, input: String, kind: Transition, duration: Option<u32>) {
+    let function = VMixInterfacer::transition(kind, duration, InputSelection::input(input));
+    co.lock().await.vmix_queue.add(std::iter::once(function));
+}
+
+/// # Media
+/// Play, pause or restart a video or audio input
+#[openapi(tag = "VMix")]
+#[post("/vmix/media/<input>?<action>")]
+pub async fn media(co: Coordinator, input: String, action: MediaAction) {
+    let function = VMixInterfacer::media(action, InputSelection::input(input));
+    co.lock().await.vmix_queue.add(std::iter::once(function));
+}
+
+/// # Select list item
+/// Select the item of a list input. `index` starts at 1
+#[openapi(tag = "VMix")]
+#[post("/vmix/list/<input>/select/<index>")]
+pub async fn select_index(co: Coordinator, input: String, index: usize) {
+    let function = VMixInterfacer::select_index(index, InputSelection::input(input));
+    co.lock().await.vmix_queue.add(std::iter::once(function));
+}
+
+/// # Set countdown
+/// Set the countdown of a title field, like `Timer.Text`. `time` is formatted like `00:10:00`
+#[openapi(tag = "VMix")]
+#[post("/vmix/countdown/<input>?<field>&<time>")]
+pub async fn set_countdown(co: Coordinator, input: String, field: String, time: String) {
+    let selection = InputSelection {
+        input,
+        field: Some(field),
+    };
+    let function = VMixInterfacer::set_countdown(time, selection);
+    co.lock().await.vmix_queue.add(std::iter::once(function));
+}
+
+/// # Auto-hide rules
+/// Graphics taken in on an overlay by an action, and out again after a while
+#[openapi(tag = "VMix")]
+#[get("/vmix/auto-hide")]
+pub async fn auto_hide_rules(co: Coordinator) -> Json<HashMap<AutoHideAction, dto::AutoHideRule>> {
+    co.lock().await.auto_hide.rules().into()
+}
+
+/// # Set auto-hide rule
+/// Take the input in on the overlay when the action runs, and out after `seconds`.
+/// Running it again before then starts the time over
+#[openapi(tag = "VMix")]
+#[post("/vmix/auto-hide?<action>", data = "<rule>")]
+pub async fn set_auto_hide(
+    co: Coordinator,
+    action: AutoHideAction,
+    rule: Json<dto::AutoHideRule>,
+) -> Result<(), Error> {
+    let rule = rule.into_inner();
+    if !(1..=4).contains(&rule.overlay) {
+        return Err(Error::InvalidOverlay(rule.overlay));
+    }
+    co.lock().await.auto_hide.set(action, rule);
+    Ok(())
+}
+
+/// # Remove auto-hide rule
+/// The action no longer changes any overlay
+#[openapi(tag = "VMix")]
+#[delete("/vmix/auto-hide?<action>")]
+pub async fn remove_auto_hide(co: Coordinator, action: AutoHideAction) {
+    co.lock().await.auto_hide.remove(action)
+}
+
+/// # Mark replay
+/// Mark the last `pre_roll` seconds as a replay event in vMix Replay. Tagged with the focused player and
+/// their hole unless `player` and `hole` are given
+#[openapi(tag = "Replay")]
+#[post("/vmix/replay/mark?<label>&<pre_roll>&<player>&<hole>")]
+pub async fn mark_replay(
+    co: Coordinator,
+    label: Option<String>,
+    pre_roll: Option<u32>,
+    player: Option<String>,
+    hole: Option<u8>,
+) -> Json<dto::ReplayEvent> {
+    co.lock()
+        .await
+        .mark_replay(player, hole, label.unwrap_or("mark".to_string()), pre_roll)
+        .into()
+}
+
+/// # Mark replay in
+/// Start a replay event at the live position, for shots that are seen coming. End it with mark out
+#[openapi(tag = "Replay")]
+#[post("/vmix/replay/mark/in")]
+pub async fn mark_replay_in(co: Coordinator) {
+    let co = co.lock().await;
+    co.replay.mark_in(&co.vmix_queue)
+}
+
+/// # Mark replay out
+/// End the replay event started with mark in. Tagged like mark replay
+#[openapi(tag = "Replay")]
+#[post("/vmix/replay/mark/out?<label>&<player>&<hole>")]
+pub async fn mark_replay_out(
+    co: Coordinator,
+    label: Option<String>,
+    player: Option<String>,
+    hole: Option<u8>,
+) -> Json<dto::ReplayEvent> {
+    co.lock()
+        .await
+        .mark_replay_out(player, hole, label.unwrap_or("mark".to_string()))
+        .into()
+}
+
+/// # Replay events
+/// Every replay event marked since the coordinator was loaded
+#[openapi(tag = "Replay")]
+#[get("/vmix/replay/events")]
+pub async fn replay_events(co: Coordinator) -> Json<Vec<dto::ReplayEvent>> {
+    co.lock().await.replay.events().into()
+}
+
+/// # Replay settings
+#[openapi(tag = "Replay")]
+#[get("/vmix/replay/settings")]
+pub async fn replay_settings(co: Coordinator) -> Json<dto::ReplaySettings> {
+    co.lock().await.replay.settings().clone().into()
+}
+
+/// # Set replay settings
+/// The pre-roll, and which highlight kinds are marked by themselves
+#[openapi(tag = "Replay")]
+#[post("/vmix/replay/settings", data = "<settings>")]
+pub async fn set_replay_settings(co: Coordinator, settings: Json<dto::ReplaySettings>) {
+    co.lock().await.replay.set_settings(settings.into_inner())
+}
+
+/// # Set theme
+/// Use the colours of another theme, and send every colour again
+#[openapi(tag = "Theme")]
+#[post("/theme/<name>")]
+pub async fn set_theme(co: Coordinator, name: &str) -> Result<(), Error> {
+    let mut co = co.lock().await;
+    co.themes.set_active(name)?;
+    co.resend_colours()
+}
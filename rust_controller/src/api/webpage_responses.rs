@@ -7,7 +7,9 @@ use rocket_okapi::openapi;
 use serde_json::json;
 
 use crate::api::guard::CoordinatorLoader;
-use crate::api::websocket::{hole_finished_alert, HoleFinishedAlert};
+use crate::api::websocket::{
+    hole_finished_alert, HighlightUpdate, HoleFinishedAlert, LeaderboardCycleUpdate,
+};
 use crate::api::{
     mutation, query, Coordinator, DivisionUpdate, Error, GeneralChannel, PlayerManagerUpdate,
 };
@@ -45,6 +47,9 @@ pub async fn load(
     loader: &State<CoordinatorLoader>,
     builder: Form<CoordinatorBuilder>,
     hole_finished_alert: GeneralChannel<HoleFinishedAlert>,
+    cycle_updater: GeneralChannel<LeaderboardCycleUpdate>,
+    highlight_updater: GeneralChannel<HighlightUpdate>,
+    player_updater: GeneralChannel<PlayerManagerUpdate>,
 ) -> Result<Template, Error> {
     let coordinator = builder.into_inner().into_coordinator().await?;
     let groups = coordinator
@@ -56,7 +61,12 @@ pub async fn load(
         .collect_vec();
     *loader.0.lock().await = Some(
         coordinator
-            .into_coordinator(hole_finished_alert.clone())
+            .into_coordinator(
+                hole_finished_alert.clone(),
+                cycle_updater,
+                highlight_updater,
+                player_updater,
+            )
             .await,
     );
     Ok(Template::render("index", json!({"groups": groups})))
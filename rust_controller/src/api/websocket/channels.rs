@@ -162,6 +162,58 @@ impl ChannelAttributes for LeaderboardRoundUpdate {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardCycleUpdate {
+    cycle: dto::LeaderboardCycleState,
+}
+
+impl From<&FlipUpVMixCoordinator> for LeaderboardCycleUpdate {
+    fn from(coordinator: &FlipUpVMixCoordinator) -> Self {
+        Self {
+            cycle: coordinator.cycle_settings.to_dto(),
+        }
+    }
+}
+
+impl ChannelAttributes for LeaderboardCycleUpdate {
+    fn try_into_message(self) -> Option<Message> {
+        Some(Message::from(serde_json::to_string(&self.cycle).ok()?))
+    }
+
+    fn make_html(self, metadata: &Metadata) -> Option<Message> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HighlightUpdate {
+    highlights: Vec<dto::Highlight>,
+}
+
+impl HighlightUpdate {
+    pub fn new(highlights: Vec<dto::Highlight>) -> Self {
+        Self { highlights }
+    }
+}
+
+impl From<&FlipUpVMixCoordinator> for HighlightUpdate {
+    fn from(coordinator: &FlipUpVMixCoordinator) -> Self {
+        Self {
+            highlights: coordinator.highlights(0),
+        }
+    }
+}
+
+impl ChannelAttributes for HighlightUpdate {
+    fn try_into_message(self) -> Option<Message> {
+        Some(Message::from(serde_json::to_string(&self.highlights).ok()?))
+    }
+
+    fn make_html(self, metadata: &Metadata) -> Option<Message> {
+        None
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum HoleFinishedAlert {
     JustFinished,
@@ -8,8 +8,8 @@ use rocket_ws::Message;
 use serde::Deserialize;
 
 pub use channels::{
-    ChannelAttributes, DivisionUpdate, HoleFinishedAlert, LeaderboardRoundUpdate,
-    PlayerManagerUpdate,
+    ChannelAttributes, DivisionUpdate, HighlightUpdate, HoleFinishedAlert, LeaderboardCycleUpdate,
+    LeaderboardRoundUpdate, PlayerManagerUpdate,
 };
 
 use crate::api::websocket::channels::{GeneralChannel, HoleUpdate};
@@ -95,6 +95,24 @@ pub async fn hole_finished_alert(
 ) -> ws::Channel<'static> {
     make_watcher_websocket(ws, watcher, shutdown).await
 }
+#[get("/leaderboard/cycle/watch")]
+pub async fn leaderboard_cycle_watcher(
+    ws: ws::WebSocket,
+    watcher: GeneralChannel<LeaderboardCycleUpdate>,
+    shutdown: Shutdown,
+) -> ws::Channel<'static> {
+    make_watcher_websocket(ws, watcher, shutdown).await
+}
+
+#[get("/highlights/watch")]
+pub async fn highlight_watcher(
+    ws: ws::WebSocket,
+    watcher: GeneralChannel<HighlightUpdate>,
+    shutdown: Shutdown,
+) -> ws::Channel<'static> {
+    make_watcher_websocket(ws, watcher, shutdown).await
+}
+
 #[derive(Deserialize, Debug)]
 struct Interpreter {
     message: String,
@@ -0,0 +1,185 @@
+//! Where graphics assets and local files are, read from the `assets` section of `Rocket.toml`.
+//!
+//! Each OS has its own table, `assets.windows` or `assets.unix`, so one config works on both.
+//! Relative folders are under `root`. Image paths are sent to vMix as they are, so on a machine
+//! without vMix, `root` should be where the vMix machine has the assets:
+//!
+//! ```toml
+//! [default.assets.unix]
+//! root = "C:\\livegrafik-flipup"
+//! image_cache = "/home/flipup/images"
+//! static_files = "/home/flipup/static"
+//!
+//! [default.assets.unix.animations]
+//! ace = "ace.mov"
+//! ```
+//!
+//! Anything left out keeps the default for the OS.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use rocket::figment::Figment;
+use serde::Deserialize;
+
+static ASSETS: OnceLock<Assets> = OnceLock::new();
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Assets {
+    root: PathBuf,
+    /// Arrows, flames and other images set on titles
+    gfx: PathBuf,
+    /// Flames and blank of the hot round on the leaderboard title
+    hot_round_images: PathBuf,
+    /// Position arrows and blank of the leaderboard property title
+    arrow_images: PathBuf,
+    hole_maps: PathBuf,
+    /// Player images, see [`crate::controller::player_image`]
+    image_cache: PathBuf,
+    /// Width and height the player images are cropped to, the size of the image on the titles
+    player_image_size: (u32, u32),
+    static_files: PathBuf,
+    pub animations: Animations,
+}
+
+impl Default for Assets {
+    fn default() -> Self {
+        if cfg!(target_os = "windows") {
+            Self {
+                root: PathBuf::from("C:\\livegrafik-flipup"),
+                gfx: Path::new("images").join("gfx"),
+                hot_round_images: PathBuf::from("X:\\FLIPUP\\grafik"),
+                arrow_images: PathBuf::new(),
+                hole_maps: PathBuf::from("holemaps"),
+                image_cache: Path::new("_conf").join("images"),
+                player_image_size: (320, 320),
+                static_files: Path::new("_conf").join("static"),
+                animations: Animations::default(),
+            }
+        } else {
+            Self {
+                root: PathBuf::from("."),
+                gfx: Path::new("images").join("gfx"),
+                hot_round_images: Path::new("images").join("gfx"),
+                arrow_images: Path::new("images").join("gfx"),
+                hole_maps: PathBuf::from("holemaps"),
+                image_cache: PathBuf::from("images"),
+                player_image_size: (320, 320),
+                static_files: PathBuf::from("static"),
+                animations: Animations::default(),
+            }
+        }
+    }
+}
+
+/// Names of the animation inputs played on overlay 4
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Animations {
+    pub ace: String,
+    pub albatross: String,
+    pub eagle: String,
+    pub birdie: String,
+    pub par: String,
+    pub bogey: String,
+    pub double_bogey: String,
+    pub triple_bogey: String,
+    /// Four or more over par
+    pub ouch: String,
+    pub ob: String,
+}
+
+impl Default for Animations {
+    fn default() -> Self {
+        Self {
+            ace: "00 ace.mov".to_string(),
+            albatross: "01 albatross.mov".to_string(),
+            eagle: "02 eagle.mov".to_string(),
+            birdie: "03 birdie.mov".to_string(),
+            par: "04 par.mov".to_string(),
+            bogey: "10 bogey.mov".to_string(),
+            double_bogey: "20 2xBogey.mov".to_string(),
+            triple_bogey: "30 3xBogey.mov".to_string(),
+            ouch: "40 ouch.mov".to_string(),
+            ob: "50 ob.mov".to_string(),
+        }
+    }
+}
+
+/// Reads the section for this OS from the Rocket config.
+///
+/// Panics if the paths are already set, as anything that used them before has the defaults
+pub fn load(figment: &Figment) {
+    if ASSETS.set(Assets::load(figment)).is_err() {
+        panic!("Asset paths were used before they were loaded from the config");
+    }
+}
+
+/// The loaded paths, the defaults for the OS if [`load`] has not been called
+pub fn get() -> &'static Assets {
+    ASSETS.get_or_init(Assets::default)
+}
+
+impl Assets {
+    fn load(figment: &Figment) -> Self {
+        let section = if cfg!(target_os = "windows") {
+            "assets.windows"
+        } else {
+            "assets.unix"
+        };
+        match figment.extract_inner(section) {
+            Ok(assets) => assets,
+            Err(e) if e.missing() => Self::default(),
+            Err(e) => {
+                warn!("Unable to read {section}: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// Path of an image in the gfx folder
+    pub fn gfx(&self, file: &str) -> String {
+        self.resolve(&self.gfx)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn hot_round_image(&self, file: &str) -> String {
+        self.resolve(&self.hot_round_images)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn arrow_image(&self, file: &str) -> String {
+        self.resolve(&self.arrow_images)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn hole_map(&self, file: &str) -> String {
+        self.resolve(&self.hole_maps)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn image_cache(&self) -> PathBuf {
+        self.resolve(&self.image_cache)
+    }
+
+    pub fn player_image_size(&self) -> (u32, u32) {
+        self.player_image_size
+    }
+
+    pub fn static_files(&self) -> PathBuf {
+        self.resolve(&self.static_files)
+    }
+}
@@ -0,0 +1,100 @@
+//! Stand-in for a CasparCG server, for running the coordinator with a CasparCG target without one.
+//!
+//! Answers `CG` commands with `202 CG OK`, and writes every command with its template data to
+//! stdout and a JSON lines log. Point a target with the `casparcg` backend at `127.0.0.1:<port>`.
+
+use std::sync::Arc;
+
+use mock_server::MockServer;
+
+mod mock_server;
+
+/// A parsed `CG <channel>-<layer> <command> <cg layer> ...` line
+struct CgCommand<'a> {
+    target: &'a str,
+    command: &'a str,
+    /// The last quoted parameter, the template data of ADD and UPDATE
+    data: Option<String>,
+}
+
+impl<'a> CgCommand<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut parts = line.strip_prefix("CG ")?.splitn(3, ' ');
+        let target = parts.next()?;
+        let command = parts.next()?;
+        let data = match command {
+            "ADD" | "UPDATE" => Self::last_quoted(parts.next().unwrap_or_default()),
+            _ => None,
+        };
+        Some(Self {
+            target,
+            command,
+            data,
+        })
+    }
+
+    fn last_quoted(parameters: &str) -> Option<String> {
+        let end = parameters.rfind('"')?;
+        let mut start = end;
+        // Find the opening quote, skipping escaped ones
+        loop {
+            start = parameters[..start].rfind('"')?;
+            if !parameters[..start].ends_with('\\') {
+                break;
+            }
+        }
+        Some(
+            parameters[start + 1..end]
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\"),
+        )
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let matches = mock_server::cli(
+        "mock_casparcg",
+        "Mock of the CasparCG AMCP protocol",
+        "5250",
+        "mock_casparcg_log.jsonl",
+    )
+    .get_matches();
+    let server = Arc::new(MockServer::new("CasparCG", &matches));
+
+    let port = *matches.get_one::<u16>("port").unwrap();
+    server
+        .clone()
+        .run(port, move |line| respond(line, &server))
+        .await
+}
+
+fn respond(line: &str, server: &MockServer) -> String {
+    let command = CgCommand::parse(line);
+    let response = match &command {
+        _ if line == "VERSION" => "201 VERSION OK\r\n2.3.3 mock".to_string(),
+        None => {
+            let name = line.split(' ').next().unwrap_or(line);
+            format!("400 {name} ERROR")
+        }
+        Some(_) if server.inject_error() => "500 FAILED".to_string(),
+        Some(_) => "202 CG OK".to_string(),
+    };
+
+    let data = command
+        .as_ref()
+        .and_then(|command| command.data.as_ref())
+        .map(|data| serde_json::from_str(data).unwrap_or(serde_json::Value::String(data.clone())));
+    server.log(
+        line,
+        &response,
+        serde_json::json!({
+            "target": command.as_ref().map(|command| command.target),
+            "command": command.as_ref().map(|command| command.command),
+            "data": data,
+            "line": line,
+            "response": response,
+        }),
+    );
+    response
+}
@@ -0,0 +1,138 @@
+//! What the mock binaries share: a line based TCP server with latency and injected errors, and a
+//! JSON lines log of every command.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+pub struct MockServer {
+    name: &'static str,
+    latency: Duration,
+    error_rate: f64,
+    log: Mutex<Option<File>>,
+}
+
+/// The arguments every mock has, add the rest before calling `get_matches`
+pub fn cli(
+    name: &'static str,
+    about: &'static str,
+    port: &'static str,
+    log: &'static str,
+) -> Command {
+    Command::new(name)
+        .about(about)
+        .arg(
+            Arg::new("port")
+                .long("port")
+                .default_value(port)
+                .value_parser(value_parser!(u16)),
+        )
+        .arg(
+            Arg::new("latency")
+                .long("latency")
+                .help("Milliseconds before every answer")
+                .default_value("0")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("error-rate")
+                .long("error-rate")
+                .help("Share of commands answered with an error, 0 to 1")
+                .default_value("0")
+                .value_parser(value_parser!(f64)),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .help("File every command is appended to")
+                .default_value(log)
+                .value_parser(value_parser!(PathBuf)),
+        )
+}
+
+impl MockServer {
+    /// `name` is what the mock stands in for, e.g. `vMix`
+    pub fn new(name: &'static str, matches: &ArgMatches) -> Self {
+        let log = File::options()
+            .create(true)
+            .append(true)
+            .open(matches.get_one::<PathBuf>("log").unwrap())
+            .map_err(|e| eprintln!("Not logging to file: {e}"))
+            .ok();
+        Self {
+            name,
+            latency: Duration::from_millis(*matches.get_one::<u64>("latency").unwrap()),
+            error_rate: *matches.get_one::<f64>("error-rate").unwrap(),
+            log: Mutex::new(log),
+        }
+    }
+
+    /// True for the share of commands set by `--error-rate`
+    pub fn inject_error(&self) -> bool {
+        self.error_rate > 0. && rand::random::<f64>() < self.error_rate
+    }
+
+    /// Prints the command with its response, and appends the entry to the log with a timestamp
+    pub fn log(&self, command: &str, response: &str, mut entry: serde_json::Value) {
+        let timestamp = chrono::Local::now();
+        println!(
+            "{} {command} -> {response}",
+            timestamp.format("%H:%M:%S%.3f")
+        );
+        if let Some(log) = &mut *self.log.lock().unwrap() {
+            entry["timestamp"] = timestamp.to_rfc3339().into();
+            if let Err(e) = writeln!(log, "{entry}") {
+                eprintln!("Unable to write to the log: {e}");
+            }
+        }
+    }
+
+    /// Answers every line from every client with `respond`, until the listener fails
+    pub async fn run(
+        self: Arc<Self>,
+        port: u16,
+        respond: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> std::io::Result<()> {
+        let respond = Arc::new(respond);
+        let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+        println!("Mock {} listening on port {port}", self.name);
+        loop {
+            let (stream, address) = listener.accept().await?;
+            println!("{address} connected");
+            let server = self.clone();
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_client(stream, &*respond).await {
+                    println!("{address} disconnected: {e}");
+                }
+            });
+        }
+    }
+
+    async fn handle_client(
+        &self,
+        stream: TcpStream,
+        respond: &(impl Fn(&str) -> String + Sync),
+    ) -> std::io::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            tokio::time::sleep(self.latency).await;
+            let response = respond(line);
+            writer
+                .write_all(format!("{response}\r\n").as_bytes())
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Stand-in for the vMix TCP API, for running the coordinator without vMix.
+//!
+//! Answers `FUNCTION ...` with `FUNCTION OK Completed` or `FUNCTION ER <reason>`, and writes every
+//! command with a timestamp to stdout and a JSON lines log. Point the coordinator at it by loading
+//! with `127.0.0.1:<port>` as the vMix IP.
+//!
+//! With `--titles`, only the inputs and fields in the file exist, like in a real vMix project:
+//!
+//! ```json
+//! { "93bd7038-5ab9-48a4-b53d-717666579b32": ["p1s1.Text", "p1h1.Fill.Color"] }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches};
+
+use mock_server::MockServer;
+
+mod mock_server;
+
+struct Settings {
+    fail_on: Vec<String>,
+    titles: Option<HashMap<String, HashSet<String>>>,
+}
+
+/// What the mock knows about vMix: the last value of every field
+type Fields = Mutex<HashMap<(String, String), String>>;
+
+struct Function<'a> {
+    name: &'a str,
+    input: Option<&'a str>,
+    field: Option<&'a str>,
+    value: Option<&'a str>,
+}
+
+impl<'a> Function<'a> {
+    fn parse(function: &'a str) -> Self {
+        let (name, query) = function.split_once(' ').unwrap_or((function, ""));
+        let mut input = None;
+        let mut field = None;
+        let mut value = None;
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("Input", v)) => input = input.or(Some(v)),
+                Some(("SelectedName", v)) => field = field.or(Some(v)),
+                // The coordinator can send the value twice, the first one is used
+                Some(("Value", v)) => value = value.or(Some(v)),
+                _ => (),
+            }
+        }
+        Self {
+            name,
+            input,
+            field,
+            value,
+        }
+    }
+}
+
+fn cli() -> ArgMatches {
+    mock_server::cli(
+        "mock_vmix",
+        "Mock of the vMix TCP API",
+        "8099",
+        "mock_vmix_log.jsonl",
+    )
+    .arg(
+        Arg::new("fail-on")
+            .long("fail-on")
+            .help("Answer functions containing this text with an error")
+            .action(ArgAction::Append),
+    )
+    .arg(
+        Arg::new("titles")
+            .long("titles")
+            .help("JSON file with the fields of every title input")
+            .value_parser(value_parser!(PathBuf)),
+    )
+    .get_matches()
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let matches = cli();
+    let titles = match matches.get_one::<PathBuf>("titles") {
+        Some(path) => {
+            let titles: HashMap<String, HashSet<String>> =
+                serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            Some(titles)
+        }
+        None => None,
+    };
+    let settings = Settings {
+        fail_on: matches
+            .get_many::<String>("fail-on")
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default(),
+        titles,
+    };
+    let server = Arc::new(MockServer::new("vMix", &matches));
+    let fields = Fields::default();
+
+    let port = *matches.get_one::<u16>("port").unwrap();
+    server
+        .clone()
+        .run(port, move |line| respond(line, &server, &settings, &fields))
+        .await
+}
+
+fn respond(line: &str, server: &MockServer, settings: &Settings, fields: &Fields) -> String {
+    let Some(function) = line.strip_prefix("FUNCTION ") else {
+        let command = line.split(' ').next().unwrap_or(line);
+        return format!("{command} ER Not supported by the mock");
+    };
+    let parsed = Function::parse(function);
+    let result = check(function, &parsed, server, settings);
+
+    if result.is_ok() {
+        if let (Some(input), Some(field)) = (parsed.input, parsed.field) {
+            fields.lock().unwrap().insert(
+                (input.to_string(), field.to_string()),
+                parsed.value.unwrap_or_default().to_string(),
+            );
+        }
+    }
+    let response = match &result {
+        Ok(()) => "FUNCTION OK Completed".to_string(),
+        Err(e) => format!("FUNCTION ER {e}"),
+    };
+    server.log(
+        function,
+        &response,
+        serde_json::json!({
+            "function": parsed.name,
+            "input": parsed.input,
+            "field": parsed.field,
+            "value": parsed.value,
+            "ok": result.is_ok(),
+            "response": response,
+        }),
+    );
+    response
+}
+
+fn check(
+    function: &str,
+    parsed: &Function,
+    server: &MockServer,
+    settings: &Settings,
+) -> Result<(), String> {
+    if let Some(text) = settings
+        .fail_on
+        .iter()
+        .find(|text| function.contains(*text))
+    {
+        return Err(format!("Injected error for \"{text}\""));
+    }
+    if server.inject_error() {
+        return Err("Injected random error".to_string());
+    }
+    let (Some(titles), Some(input)) = (&settings.titles, parsed.input) else {
+        return Ok(());
+    };
+    let fields = titles
+        .get(input)
+        .ok_or(format!("Input {input} not found"))?;
+    match parsed.field {
+        Some(field) if !fields.contains(field) => Err(format!("Field {field} not found")),
+        _ => Ok(()),
+    }
+}
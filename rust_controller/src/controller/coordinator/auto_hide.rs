@@ -0,0 +1,86 @@
+//! Takes graphics off air again a while after an action put them on.
+//!
+//! Every rule takes an input in on an overlay channel when its action runs, and out again after
+//! its duration. Another action on the same overlay cancels the pending take-out and starts it over.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::dto;
+use crate::dto::AutoHideAction;
+use crate::json_file::{load_json_file, save_json_file};
+use crate::vmix::functions::{InputSelection, OverlayAction, VMixInterfacer};
+use crate::vmix::{journal, VMixQueue};
+
+pub const AUTO_HIDE_FILE: &str = "auto_hide.json";
+
+#[derive(Debug, Clone, Default)]
+pub struct AutoHide {
+    rules: HashMap<AutoHideAction, dto::AutoHideRule>,
+    /// Pending take-outs by overlay channel
+    timers: Arc<Mutex<HashMap<u8, JoinHandle<()>>>>,
+}
+
+impl AutoHide {
+    pub fn load() -> Self {
+        Self {
+            rules: load_json_file(AUTO_HIDE_FILE),
+            ..Default::default()
+        }
+    }
+
+    fn save(&self) {
+        save_json_file(AUTO_HIDE_FILE, &self.rules);
+    }
+
+    pub fn rules(&self) -> HashMap<AutoHideAction, dto::AutoHideRule> {
+        self.rules.clone()
+    }
+
+    pub fn set(&mut self, action: AutoHideAction, rule: dto::AutoHideRule) {
+        self.rules.insert(action, rule);
+        self.save();
+    }
+
+    pub fn remove(&mut self, action: AutoHideAction) {
+        self.rules.remove(&action);
+        self.save();
+    }
+
+    /// Stops the pending take-out of the overlay, for when it is changed by hand
+    pub fn cancel(&self, overlay: u8) {
+        if let Some(timer) = self.timers.lock().unwrap().remove(&overlay) {
+            timer.abort();
+        }
+    }
+
+    /// Takes the input of the action in, and schedules it to be taken out. Does nothing if the action has no rule
+    pub fn trigger(&self, action: AutoHideAction, queue: &Arc<VMixQueue>) {
+        let Some(rule) = self.rules.get(&action) else {
+            return;
+        };
+        let overlay = rule.overlay;
+        queue.add(std::iter::once(VMixInterfacer::overlay(
+            overlay,
+            OverlayAction::In,
+            Some(InputSelection::input(rule.input.clone())),
+        )));
+
+        let queue = queue.clone();
+        let duration = Duration::from_secs_f64(rule.seconds.max(0.));
+        let timer = tokio::spawn(journal::CAUSE.scope("auto hide".to_string(), async move {
+            tokio::time::sleep(duration).await;
+            queue.add(std::iter::once(VMixInterfacer::<InputSelection>::overlay(
+                overlay,
+                OverlayAction::Out,
+                None,
+            )));
+        }));
+        if let Some(previous) = self.timers.lock().unwrap().insert(overlay, timer) {
+            previous.abort();
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use crate::controller::coordinator::player::Player;
+use crate::controller::queries::results_getter::HoleResult;
+use crate::controller::queries::Division;
+use crate::dto::{Highlight, HighlightKind};
+use crate::flipup_vmix_controls::{Leaderboard, LeaderboardPlayer};
+
+/// Birdie streaks shorter than this are not worth mentioning
+const MIN_BIRDIE_STREAK: usize = 3;
+/// Round lows count once the player has finished this many holes, before that everyone is close to even
+const MIN_ROUND_LOW_HOLES: u8 = 6;
+
+/// A hole result from Tjing that is new or has a different score than before
+#[derive(Debug, Clone)]
+pub struct ScoreChange {
+    pub player_id: String,
+    pub result: HoleResult,
+}
+
+/// Turns score changes into highlights.
+///
+/// Remembers the round low and the leaders of every division, so only changes to them are reported.
+/// A hole only gives each kind of highlight once, so a corrected score does not repeat it.
+#[derive(Debug, Clone, Default)]
+pub struct HighlightDetector {
+    /// By division name and round
+    round_lows: HashMap<(String, usize), isize>,
+    leaders: HashMap<String, Vec<String>>,
+    /// Player and round
+    bogey_free: HashSet<(String, usize)>,
+    /// Player, round, hole and kind of every highlight on a hole
+    reported: HashSet<(String, usize, u8, &'static str)>,
+    highlights: Vec<Highlight>,
+}
+
+impl HighlightDetector {
+    /// Records the current standings without reporting them, so nothing that already happened gets announced.
+    pub fn seed(
+        &mut self,
+        players: &[&Player],
+        leaderboard: &Leaderboard,
+        divisions: &[Arc<Division>],
+        round: usize,
+    ) {
+        for division in divisions {
+            let lb_players = leaderboard.all_players_in_div(division.clone(), round);
+            if let Some(low) = Self::round_low(&lb_players) {
+                self.round_lows
+                    .insert((division.name.clone(), round), low.round_score);
+            }
+            self.leaders
+                .insert(division.name.clone(), Self::leaders(&lb_players));
+        }
+        self.bogey_free.extend(
+            players
+                .iter()
+                .filter(|player| Self::finished_bogey_free(player))
+                .map(|player| (player.player_id.clone(), round)),
+        );
+    }
+
+    /// Returns the highlights that the changes caused, they are also kept in the list of all highlights.
+    pub fn detect(
+        &mut self,
+        changes: &[ScoreChange],
+        players: &[&Player],
+        leaderboard: &Leaderboard,
+        round: usize,
+    ) -> Vec<Highlight> {
+        let first_new = self.highlights.len();
+        let mut divisions: Vec<Arc<Division>> = vec![];
+        for change in changes {
+            let Some(player) = players
+                .iter()
+                .find(|player| player.player_id == change.player_id)
+            else {
+                continue;
+            };
+            if !divisions.contains(&player.division) {
+                divisions.push(player.division.clone());
+            }
+            self.detect_hole(player, &change.result, round);
+        }
+        for division in divisions {
+            self.detect_standings(division, leaderboard, round);
+        }
+        self.highlights[first_new..].to_vec()
+    }
+
+    pub fn since(&self, id: usize) -> &[Highlight] {
+        self.highlights.get(id..).unwrap_or_default()
+    }
+
+    fn detect_hole(&mut self, player: &Player, result: &HoleResult, round: usize) {
+        // Scores of 0 are holes that have not been entered yet
+        if result.score == 0 {
+            return;
+        }
+        let hole = Some(result.hole_number as u8);
+        let to_par = result.score as isize - result.par as isize;
+        let kind = match to_par {
+            _ if result.score == 1 => Some(HighlightKind::Ace),
+            ..=-3 => Some(HighlightKind::Albatross),
+            -2 => Some(HighlightKind::Eagle),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.push(player, round, hole, kind);
+        }
+        // Made from outside circle 1
+        if result.is_outside_putt {
+            self.push(player, round, hole, HighlightKind::LongPutt);
+        }
+
+        if to_par < 0 {
+            let streak = player.results.under_par_streak();
+            if streak >= MIN_BIRDIE_STREAK {
+                self.push(player, round, hole, HighlightKind::BirdieStreak { streak });
+            }
+        }
+
+        if Self::finished_bogey_free(player)
+            && self.bogey_free.insert((player.player_id.clone(), round))
+        {
+            self.push(player, round, None, HighlightKind::BogeyFreeRound);
+        }
+    }
+
+    fn detect_standings(
+        &mut self,
+        division: Arc<Division>,
+        leaderboard: &Leaderboard,
+        round: usize,
+    ) {
+        let lb_players = leaderboard.all_players_in_div(division.clone(), round);
+        let find = |id: &str| lb_players.iter().find(|player| player.id == id);
+
+        // Like the hot round, the first round's low is the leader, which is already reported
+        if let Some(low) = Self::round_low(&lb_players).filter(|_| round != 0) {
+            let key = (division.name.clone(), round);
+            if self.is_new_round_low(key, low.round_score) {
+                self.push_lb(
+                    low,
+                    &division,
+                    round,
+                    HighlightKind::RoundLow {
+                        score: low.round_score,
+                    },
+                );
+            }
+        }
+
+        let leaders = Self::leaders(&lb_players);
+        let Some(previous) = self.leaders.insert(division.name.clone(), leaders.clone()) else {
+            return;
+        };
+        if leaders.is_empty() || leaders == previous {
+            return;
+        }
+        match leaders.as_slice() {
+            [leader] => {
+                let Some(leader) = find(leader) else { return };
+                let previous_leader = match previous.as_slice() {
+                    [previous] => find(previous).map(|player| player.name.clone()),
+                    _ => None,
+                };
+                self.push_lb(
+                    leader,
+                    &division,
+                    round,
+                    HighlightKind::LeadChange { previous_leader },
+                );
+            }
+            tied => {
+                // The player who just joined the tie is the interesting one
+                let Some(player) = tied
+                    .iter()
+                    .find(|id| !previous.contains(id))
+                    .or(tied.first())
+                    .and_then(|id| find(id))
+                else {
+                    return;
+                };
+                self.push_lb(
+                    player,
+                    &division,
+                    round,
+                    HighlightKind::TiedForLead {
+                        players: tied.len(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Lowest round of the players who have played enough holes for it to mean something
+    fn round_low(lb_players: &[LeaderboardPlayer]) -> Option<&LeaderboardPlayer> {
+        lb_players
+            .iter()
+            .filter(|player| player.thru() >= MIN_ROUND_LOW_HOLES)
+            .min_by_key(|player| player.round_score)
+    }
+
+    /// Remembers the low, the first one is only remembered since there is nothing to beat
+    fn is_new_round_low(&mut self, key: (String, usize), score: isize) -> bool {
+        match self.round_lows.get(&key).copied() {
+            Some(previous) if score >= previous => false,
+            previous => {
+                self.round_lows.insert(key, score);
+                previous.is_some()
+            }
+        }
+    }
+
+    fn leaders(lb_players: &[LeaderboardPlayer]) -> Vec<String> {
+        lb_players
+            .iter()
+            .filter(|player| player.position == 1)
+            .map(|player| player.id.clone())
+            .sorted()
+            .collect()
+    }
+
+    fn finished_bogey_free(player: &Player) -> bool {
+        player.results.amount_of_holes_finished() == 18 && player.results.is_bogey_free()
+    }
+
+    fn push(&mut self, player: &Player, round: usize, hole: Option<u8>, kind: HighlightKind) {
+        self.add(
+            player.player_id.clone(),
+            player.name.clone(),
+            &player.division,
+            round,
+            hole,
+            kind,
+        )
+    }
+
+    fn push_lb(
+        &mut self,
+        player: &LeaderboardPlayer,
+        division: &Division,
+        round: usize,
+        kind: HighlightKind,
+    ) {
+        self.add(
+            player.id.clone(),
+            player.name.clone(),
+            division,
+            round,
+            None,
+            kind,
+        )
+    }
+
+    fn add(
+        &mut self,
+        player_id: String,
+        player_name: String,
+        division: &Division,
+        round: usize,
+        hole: Option<u8>,
+        kind: HighlightKind,
+    ) {
+        if let Some(hole) = hole {
+            if !self
+                .reported
+                .insert((player_id.clone(), round, hole, kind.name()))
+            {
+                return;
+            }
+        }
+        let description = describe(&player_name, &division.name, round, hole, &kind);
+        self.highlights.push(Highlight {
+            id: self.highlights.len(),
+            player_id,
+            player_name,
+            division: division.name.clone(),
+            round,
+            hole,
+            timestamp: chrono::Utc::now().timestamp(),
+            kind,
+            description,
+        });
+    }
+}
+
+fn describe(
+    name: &str,
+    division: &str,
+    round: usize,
+    hole: Option<u8>,
+    kind: &HighlightKind,
+) -> String {
+    let hole = hole.map(|hole| hole.to_string()).unwrap_or_default();
+    match kind {
+        HighlightKind::Ace => format!("{name} just aced hole {hole}!"),
+        HighlightKind::Albatross => format!("{name} just made an albatross on hole {hole}"),
+        HighlightKind::Eagle => format!("{name} just eagled {hole}"),
+        HighlightKind::LongPutt => format!("{name} made a long putt on hole {hole}"),
+        HighlightKind::BirdieStreak { streak } => {
+            format!("{name} has {streak} birdies in a row")
+        }
+        HighlightKind::BogeyFreeRound => {
+            format!("{name} finished round {} bogey free", round + 1)
+        }
+        HighlightKind::RoundLow { score } => {
+            format!("{name} has the lowest round in {division} at {score:+}")
+        }
+        HighlightKind::LeadChange { .. } => format!("{name} takes the lead in {division}"),
+        HighlightKind::TiedForLead { players } => {
+            format!("{name} is tied for the lead in {division}, {players} players share it")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(round: usize) -> (String, usize) {
+        ("MPO".to_string(), round)
+    }
+
+    #[test]
+    fn first_low_is_only_remembered() {
+        let mut detector = HighlightDetector::default();
+        assert!(!detector.is_new_round_low(key(1), -3));
+        assert!(detector.is_new_round_low(key(1), -4));
+        assert!(!detector.is_new_round_low(key(1), -4));
+        assert!(!detector.is_new_round_low(key(1), -2));
+    }
+
+    #[test]
+    fn round_lows_are_kept_per_round() {
+        let mut detector = HighlightDetector::default();
+        detector.is_new_round_low(key(1), -8);
+        assert!(!detector.is_new_round_low(key(2), -3));
+        assert!(detector.is_new_round_low(key(2), -4));
+    }
+
+    #[test]
+    fn corrected_hole_is_not_reported_again() {
+        let mut detector = HighlightDetector::default();
+        let division = Division::default();
+        for _ in 0..2 {
+            detector.add(
+                "id".to_string(),
+                "Name".to_string(),
+                &division,
+                1,
+                Some(7),
+                HighlightKind::Eagle,
+            );
+        }
+        detector.add(
+            "id".to_string(),
+            "Name".to_string(),
+            &division,
+            1,
+            Some(7),
+            HighlightKind::LongPutt,
+        );
+        assert_eq!(detector.since(0).len(), 2);
+    }
+}
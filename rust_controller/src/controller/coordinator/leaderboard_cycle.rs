@@ -1,32 +1,139 @@
+use crate::api::{GeneralChannel, LeaderboardCycleUpdate};
 use crate::controller::coordinator::FlipUpVMixCoordinator;
 use crate::controller::queries::Division;
+use crate::dto;
 use crate::flipup_vmix_controls::Leaderboard;
 use crate::vmix::functions::{Compare2x2, VMixInterfacer};
+use crate::vmix::journal;
 use crate::vmix::VMixQueue;
 use itertools::Itertools;
 use rayon::prelude::*;
-use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+
+pub const DEFAULT_CYCLE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Runtime controls of the leaderboard cycle.
+///
+/// Lives on the coordinator so the API can change it without touching the cycle itself,
+/// the cycle reads it before every step.
+#[derive(Clone, Debug)]
+pub struct CycleSettings {
+    pub interval: Duration,
+    pub order: Vec<Arc<Division>>,
+    pub excluded: Vec<Arc<Division>>,
+    pub paused: bool,
+    pub skip_to: Option<Arc<Division>>,
+    pub current: Option<Arc<Division>>,
+    pub featured: Option<Arc<Division>>,
+    wake: Arc<Notify>,
+}
+
+impl CycleSettings {
+    pub fn new(order: Vec<Arc<Division>>) -> Self {
+        Self {
+            interval: DEFAULT_CYCLE_INTERVAL,
+            order,
+            excluded: vec![],
+            paused: false,
+            skip_to: None,
+            current: None,
+            featured: None,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Makes the cycle stop waiting and take its next step right away
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+        self.wake();
+    }
+
+    /// Puts the given divisions first, in the given order. Divisions not mentioned keep their
+    /// relative order after them.
+    pub fn set_order(&mut self, divisions: Vec<Arc<Division>>) {
+        let rest = self
+            .order
+            .iter()
+            .filter(|div| !divisions.contains(div))
+            .cloned()
+            .collect_vec();
+        self.order = divisions.into_iter().chain(rest).collect();
+    }
+
+    pub fn exclude(&mut self, division: Arc<Division>) {
+        if !self.excluded.contains(&division) {
+            self.excluded.push(division);
+        }
+    }
+
+    pub fn include(&mut self, division: &Division) {
+        self.excluded.retain(|div| div.as_ref() != division);
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.wake();
+    }
+
+    pub fn skip_to(&mut self, division: Arc<Division>) {
+        self.skip_to = Some(division);
+        self.wake();
+    }
+
+    fn is_excluded(&self, division: &Division) -> bool {
+        self.excluded.iter().any(|div| div.as_ref() == division)
+    }
+
+    pub fn to_dto(&self) -> dto::LeaderboardCycleState {
+        dto::LeaderboardCycleState {
+            interval_seconds: self.interval.as_secs(),
+            order: self.order.iter().map(|div| div.name.clone()).collect(),
+            excluded: self.excluded.iter().map(|div| div.name.clone()).collect(),
+            paused: self.paused,
+            current: self.current.as_ref().map(|div| div.name.clone()),
+            featured: self.featured.as_ref().map(|div| div.name.clone()),
+        }
+    }
+}
 
 pub struct LeaderboardCycle {
     current_cycled: Arc<Division>,
-    all_divisions: VecDeque<Arc<Division>>,
     coordinator: Arc<Mutex<FlipUpVMixCoordinator>>,
     leaderboard: Leaderboard,
     round: usize,
     current_featured_div: Arc<Division>,
     featured_group_id: String,
+    channel: GeneralChannel<LeaderboardCycleUpdate>,
 }
 
 impl LeaderboardCycle {
-    async fn new(coordinator: Arc<Mutex<FlipUpVMixCoordinator>>) -> Self {
+    async fn new(
+        coordinator: Arc<Mutex<FlipUpVMixCoordinator>>,
+        channel: GeneralChannel<LeaderboardCycleUpdate>,
+    ) -> Self {
         let temp_coordinator = coordinator.lock().await;
-        let all_divisions = VecDeque::from(temp_coordinator.handler.get_divisions().clone());
+        let current_cycled = temp_coordinator
+            .cycle_settings
+            .order
+            .first()
+            .unwrap()
+            .clone();
         let round = temp_coordinator.round_ind;
         let mut leaderboard = temp_coordinator.handler.get_previous_leaderboards();
         leaderboard.cycle = true;
+        leaderboard.page_size = temp_coordinator.leaderboard.page_size;
+        leaderboard.set_virtual_divisions(temp_coordinator.virtual_divisions.clone());
+        leaderboard.set_themes(temp_coordinator.themes.clone());
         let featured_player = temp_coordinator
             .get_latest_player_to_soon_play_featured()
             .unwrap_or(
@@ -43,13 +150,13 @@ impl LeaderboardCycle {
         dbg!(&featured_div);
         drop(temp_coordinator);
         Self {
-            current_cycled: all_divisions.front().unwrap().clone(),
+            current_cycled,
             current_featured_div: featured_div,
-            all_divisions,
             coordinator,
             leaderboard,
             round,
             featured_group_id,
+            channel,
         }
     }
 
@@ -75,14 +182,13 @@ impl LeaderboardCycle {
         if let Some(player) = coordinator.find_player_mut(&players[0].id) {
             dbg!(&player.division);
             let holes = player.holes.clone();
-            let out = player
-                .results
-                .get_hole_info(1, stats, &holes, &division)
-                .into_iter()
-                .map(VMixInterfacer::into_featured_hole_card)
-                .collect_vec();
-
-            queue.add(out.into_iter());
+            if let Ok(info) = player.results.get_hole_info(1, stats, &holes, &division) {
+                let out = info
+                    .into_iter()
+                    .map(VMixInterfacer::into_featured_hole_card)
+                    .collect_vec();
+                queue.add(out.into_iter());
+            }
         }
         let all_players = self
             .leaderboard
@@ -95,6 +201,14 @@ impl LeaderboardCycle {
             .send_to_vmix(&self.current_featured_div, queue, self.round, true);
     }
 
+    /// Follows the page size of the leaderboard, starting over on the first page when it changes
+    fn set_page_size(&mut self, page_size: usize) {
+        if self.leaderboard.page_size != page_size {
+            self.leaderboard.page_size = page_size;
+            self.leaderboard.skip = 0;
+        }
+    }
+
     fn refresh_leaderboard(&mut self, queue: Arc<VMixQueue>) {
         self.leaderboard
             .send_to_vmix(&self.current_cycled, queue, self.round, false)
@@ -142,51 +256,103 @@ impl LeaderboardCycle {
     }
 
     pub async fn next(&mut self) {
-        let coordinator = self.coordinator.lock().await;
+        let mut coordinator = self.coordinator.lock().await;
         let queue = coordinator.vmix_queue.clone();
         self.leaderboard
             .add_state(coordinator.current_leaderboard_state());
-        drop(coordinator);
-        self.current_cycled = self.cycle_next(queue);
+        let settings = &mut coordinator.cycle_settings;
+        self.current_cycled = match settings.skip_to.take() {
+            Some(division) => {
+                self.prepare_division(&division, queue);
+                division
+            }
+            None => self.cycle_next(settings, queue),
+        };
+        settings.current = Some(self.current_cycled.clone());
+        settings.featured = Some(self.current_featured_div.clone());
+        self.channel.send_from_coordinator(&coordinator);
     }
 
-    fn cycle_next(&mut self, queue: Arc<VMixQueue>) -> Arc<Division> {
-        let div = self.all_divisions.pop_front().unwrap();
-
-        self.all_divisions.push_back(div.clone());
-        if self.current_featured_div == div {
-            warn!("skipping div in cycle due to featured");
-            return self.cycle_next(queue);
-        }
-        let all_players = self.leaderboard.all_players_in_div(div.clone(), self.round);
-        if all_players.is_empty() || all_players.iter().all(|player| player.position == 1) {
-            self.cycle_next(queue)
-        } else {
-            if all_players.len() < 6 {
-                queue.add(FlipUpVMixCoordinator::clear_little_cycling_lb().into_iter())
+    fn cycle_next(&self, settings: &CycleSettings, queue: Arc<VMixQueue>) -> Arc<Division> {
+        let start = settings
+            .order
+            .iter()
+            .position(|div| div == &self.current_cycled)
+            .map(|pos| pos + 1)
+            .unwrap_or_default();
+        let next = settings
+            .order
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(settings.order.len())
+            .filter(|div| !settings.is_excluded(div))
+            .find(|div| {
+                if self.current_featured_div == **div {
+                    warn!("skipping div in cycle due to featured");
+                    return false;
+                }
+                let all_players = self
+                    .leaderboard
+                    .all_players_in_div((*div).clone(), self.round);
+                !(all_players.is_empty() || all_players.iter().all(|player| player.position == 1))
+            })
+            .cloned();
+        match next {
+            Some(div) => {
+                self.prepare_division(&div, queue);
+                div
             }
-            div
+            // Nothing else is worth showing, stay where we are
+            None => self.current_cycled.clone(),
+        }
+    }
+
+    fn prepare_division(&self, division: &Arc<Division>, queue: Arc<VMixQueue>) {
+        let all_players = self
+            .leaderboard
+            .all_players_in_div(division.clone(), self.round);
+        if all_players.len() < 6 {
+            queue.add(FlipUpVMixCoordinator::clear_little_cycling_lb().into_iter())
         }
     }
 }
 
 pub async fn start_leaderboard_cycle(
     coordinator: Arc<Mutex<FlipUpVMixCoordinator>>,
+    channel: GeneralChannel<LeaderboardCycleUpdate>,
 ) -> Arc<Mutex<LeaderboardCycle>> {
-    let cycle = Arc::new(Mutex::new(LeaderboardCycle::new(coordinator).await));
+    let cycle = Arc::new(Mutex::new(
+        LeaderboardCycle::new(coordinator.clone(), channel).await,
+    ));
     let loop_cycle = cycle.clone();
-    tokio::spawn(async move {
-        let cycle = loop_cycle;
-        loop {
-            {
-                let mut cycle = cycle.lock().await;
-                cycle.send_featured().await;
-                cycle.next().await;
-                cycle.update_leaderboard().await;
-                dbg!(&cycle.current_featured_div);
+    tokio::spawn(
+        journal::CAUSE.scope("leaderboard cycle".to_string(), async move {
+            let cycle = loop_cycle;
+            loop {
+                let (interval, wake, should_step, page_size) = {
+                    let coordinator = coordinator.lock().await;
+                    let settings = &coordinator.cycle_settings;
+                    (
+                        settings.interval,
+                        settings.wake.clone(),
+                        !settings.paused || settings.skip_to.is_some(),
+                        coordinator.leaderboard.page_size,
+                    )
+                };
+                if should_step {
+                    let mut cycle = cycle.lock().await;
+                    cycle.set_page_size(page_size);
+                    cycle.send_featured().await;
+                    cycle.next().await;
+                    cycle.update_leaderboard().await;
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => (),
+                    _ = wake.notified() => (),
+                }
             }
-            tokio::time::sleep(Duration::from_secs(20)).await;
-        }
-    });
+        }),
+    );
     cycle
 }
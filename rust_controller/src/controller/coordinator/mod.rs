@@ -1,8 +1,14 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use flipup_vmix_controls::LeaderBoardProperty;
+use flipup_vmix_controls::{
+    ClubAffiliations, ClubStandings, ClubStandingsProperty, RoundSummary, RoundSummaryProperty,
+};
 use flipup_vmix_controls::{Leaderboard, LeaderboardState};
+use highlights::{HighlightDetector, ScoreChange};
 use itertools::Itertools;
+use leaderboard_cycle::CycleSettings;
 pub use player::Player;
 use player_queue_system::PlayerManager;
 use rayon::prelude::*;
@@ -10,21 +16,30 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use vmix::functions::VMixInterfacer;
 use vmix::functions::{VMixPlayerInfo, VMixSelectionTrait};
+use vmix::title_mapping;
 use vmix::VMixQueue;
 
 pub use super::*;
 use crate::api::{DivisionUpdate, Error, GeneralChannel, HoleUpdate, PlayerManagerUpdate};
 use crate::controller::get_data::RustHandler;
+use crate::controller::hole_history::HoleHistory;
 use crate::controller::queries::Division;
+use crate::controller::virtual_division::VirtualDivisions;
 use crate::dto::SimpleRound;
+use crate::flipup_vmix_controls::theme::Themes;
 use crate::flipup_vmix_controls::CycledLeaderboard;
 use crate::vmix::functions::Compare2x2;
 use crate::{api, vmix};
 use crate::{dto, flipup_vmix_controls};
+use auto_hide::AutoHide;
+use replay::ReplayMarker;
 
+pub mod auto_hide;
+pub mod highlights;
 pub mod leaderboard_cycle;
 pub mod player;
 mod player_queue_system;
+pub mod replay;
 mod simple_queries;
 mod vmix_calls;
 
@@ -34,7 +49,6 @@ pub struct FlipUpVMixCoordinator {
     pub leaderboard_division: Arc<Division>,
     pub leaderboard: Leaderboard,
     focused_player_index: usize,
-    ip: String,
     handler: RustHandler,
     round_ind: usize,
     current_through: u8,
@@ -47,23 +61,33 @@ pub struct FlipUpVMixCoordinator {
     pub leaderboard_round: usize,
     pub next_group: Arc<Mutex<String>>,
     pub broadcast_type: Arc<BroadcastType>,
+    pub cycle_settings: CycleSettings,
+    highlights: HighlightDetector,
+    clubs: Arc<ClubAffiliations>,
+    hole_history: HoleHistory,
+    pub auto_hide: AutoHide,
+    pub replay: ReplayMarker,
+    pub virtual_divisions: VirtualDivisions,
+    pub themes: Themes,
 }
 
 impl FlipUpVMixCoordinator {
     pub async fn new(
-        ip: String,
+        queue: VMixQueue,
         event_ids: Vec<String>,
         focused_player: usize,
         round: usize,
         featured_hole: u8,
         broadcast_type: BroadcastType,
+        leaderboard_page_size: usize,
     ) -> Result<Self, Error> {
         assert!(!event_ids.is_empty());
-        let queue = VMixQueue::new(ip.clone())?;
         let broadcast_type = Arc::new(broadcast_type);
         let handler = RustHandler::new(event_ids.clone(), round, broadcast_type.clone()).await?;
 
-        let all_divs = handler.get_divisions();
+        let virtual_divisions = VirtualDivisions::load();
+        let mut all_divs = handler.get_divisions();
+        all_divs.extend(virtual_divisions.divisions());
         let Some(first_group) = handler
             .groups
             .get(round)
@@ -96,9 +120,9 @@ impl FlipUpVMixCoordinator {
 
         let mut coordinator = FlipUpVMixCoordinator {
             leaderboard_division: all_divs.first().unwrap().clone(),
+            cycle_settings: CycleSettings::new(all_divs.clone()),
             all_divs,
             focused_player_index: focused_player,
-            ip,
             player_manager: PlayerManager::new(first_group.player_ids()),
             leaderboard: handler.get_previous_leaderboards(),
             featured_card: PlayerManager::new(card_starts_at_hole.player_ids()),
@@ -112,20 +136,49 @@ impl FlipUpVMixCoordinator {
             leaderboard_round: round,
             next_group,
             broadcast_type,
+            highlights: HighlightDetector::default(),
+            clubs: Arc::new(ClubAffiliations::load()),
+            hole_history: HoleHistory::load(),
+            auto_hide: AutoHide::load(),
+            replay: ReplayMarker::load(),
+            virtual_divisions,
+            themes: Themes::load(),
         };
+        coordinator.leaderboard.page_size = leaderboard_page_size;
+        coordinator
+            .leaderboard
+            .set_virtual_divisions(coordinator.virtual_divisions.clone());
+        coordinator
+            .leaderboard
+            .set_themes(coordinator.themes.clone());
         coordinator.handler.add_total_score_to_players();
+        coordinator.handler.add_previous_stats_to_players();
+        coordinator
+            .hole_history
+            .record(&coordinator.event_ids, &coordinator.handler.all_players());
         coordinator.vmix_function_on_card(&Player::set_name);
         coordinator.reset_score();
         Ok(coordinator)
     }
 }
 impl FlipUpVMixCoordinator {
+    /// Puts what the coordinator queues from now on on an API call, in the vMix journal.
+    /// Queues cloned before keep the cause they had
+    pub fn set_queue_cause(&mut self, cause: Option<String>) {
+        Arc::make_mut(&mut self.vmix_queue).set_cause(cause);
+    }
+
     pub fn available_players(&self) -> Vec<&Player> {
         self.handler.get_players()
     }
 
     pub fn increase_leaderboard_skip(&mut self) {
-        self.leaderboard.skip += 1;
+        let pages = self
+            .leaderboard
+            .page_count(&self.leaderboard_division, self.leaderboard_round);
+        if self.leaderboard.skip + 1 < pages {
+            self.leaderboard.skip += 1;
+        }
         self.set_leaderboard(None);
     }
 
@@ -141,6 +194,62 @@ impl FlipUpVMixCoordinator {
         self.set_leaderboard(None);
     }
 
+    /// `page` starts at 1, like in [`dto::LeaderboardPage`]
+    pub fn set_leaderboard_page(&mut self, page: usize) -> Result<(), Error> {
+        let pages = self
+            .leaderboard
+            .page_count(&self.leaderboard_division, self.leaderboard_round);
+        if page == 0 || page > pages {
+            return Err(Error::PageNotFound { page, pages });
+        }
+        self.leaderboard.skip = page - 1;
+        self.set_leaderboard(None);
+        Ok(())
+    }
+
+    /// Switches the leaderboard to the division of the player and shows the page they are on
+    pub fn set_leaderboard_page_to_player(&mut self, player_id: &str) -> Result<(), Error> {
+        let division = self
+            .available_players()
+            .into_iter()
+            .find(|player| player.player_id == player_id)
+            .map(|player| player.division.clone())
+            .ok_or(Error::PlayerNotFound(player_id.to_string()))?;
+        self.add_state_to_leaderboard();
+        let page = self
+            .leaderboard
+            .page_of_player(player_id, &division, self.leaderboard_round)
+            .ok_or(Error::PlayerNotFound(player_id.to_string()))?;
+        self.leaderboard_division = division;
+        self.leaderboard.skip = page;
+        self.set_leaderboard(None);
+        Ok(())
+    }
+
+    pub fn set_leaderboard_page_size(&mut self, page_size: usize) -> Result<(), Error> {
+        if page_size == 0 {
+            return Err(Error::InvalidPageSize(page_size));
+        }
+        self.queue_add(&FlipUpVMixCoordinator::clear_lb(self.leaderboard.page_size));
+        self.leaderboard.page_size = page_size;
+        self.leaderboard.skip = 0;
+        self.set_leaderboard(None);
+        // A running cycle picks up the new size on its next step
+        self.cycle_settings.wake();
+        Ok(())
+    }
+
+    pub fn leaderboard_page(&self) -> dto::LeaderboardPage {
+        dto::LeaderboardPage {
+            page: self.leaderboard.skip + 1,
+            pages: self
+                .leaderboard
+                .page_count(&self.leaderboard_division, self.leaderboard_round),
+            page_size: self.leaderboard.page_size,
+            division: self.leaderboard_division.name.clone(),
+        }
+    }
+
     pub fn previous_rounds_players(&self) -> Vec<&Player> {
         self.handler.get_previous_rounds_players()
     }
@@ -180,7 +289,7 @@ impl FlipUpVMixCoordinator {
         let out = self
             .focused_player_mut()
             .results
-            .get_hole_info(featured_hole, stats, &holes, div)
+            .get_hole_info(featured_hole, stats, &holes, div)?
             .into_iter()
             .map(VMixInterfacer::into_featured_hole_card)
             .collect_vec();
@@ -234,7 +343,7 @@ impl FlipUpVMixCoordinator {
 
         let current = self
             .focused_player()
-            .set_all_current_player_values(&all_values);
+            .set_all_current_player_values(&self.themes.active(), &all_values);
         self.queue_add(&all_values);
         self.queue_add(&current);
         player_updater.send_from_coordinator(self);
@@ -289,7 +398,9 @@ impl FlipUpVMixCoordinator {
             .focused_player()
             .set_all_values(&self.leaderboard, false)?;
         self.queue_add(&all);
-        let current = self.focused_player().set_all_current_player_values(&all);
+        let current = self
+            .focused_player()
+            .set_all_current_player_values(&self.themes.active(), &all);
         self.queue_add(&current);
         channel.send_from_coordinator(self);
         Ok(())
@@ -324,7 +435,7 @@ impl FlipUpVMixCoordinator {
         self.add_state_to_leaderboard();
         let player = self.focused_player();
         let all = player.set_all_values(&self.leaderboard, false)?;
-        let current = player.set_all_current_player_values(&all);
+        let current = player.set_all_current_player_values(&self.themes.active(), &all);
         self.queue_add(&all);
         self.queue_add(&current);
 
@@ -500,21 +611,63 @@ impl FlipUpVMixCoordinator {
     }
 
     pub fn find_division(&self, div_id: &str) -> Option<Arc<Division>> {
-        self.handler
-            .get_divisions()
+        self.all_divs
             .iter()
             .find(|div| div.id.inner() == div_id)
             .map(Arc::clone)
     }
 
     pub fn find_division_by_name(&self, div_name: &str) -> Option<Arc<Division>> {
-        self.handler
-            .get_divisions()
+        self.all_divs
             .iter()
             .find(|div| div.name == div_name)
             .map(Arc::clone)
     }
 
+    /// Adds the virtual division so it can be picked like any other division, also in the cycle
+    pub fn add_virtual_division(
+        &mut self,
+        definition: dto::VirtualDivision,
+    ) -> Result<Arc<Division>, Error> {
+        if definition.name.is_empty() || definition.short_name.is_empty() {
+            return Err(Error::InvalidDivision(definition.name));
+        }
+        let real_divisions = self.handler.get_divisions();
+        if real_divisions.iter().any(|div| div.name == definition.name) {
+            return Err(Error::InvalidDivision(definition.name));
+        }
+        if real_divisions
+            .iter()
+            .any(|div| div.short_name == definition.short_name)
+        {
+            return Err(Error::DuplicateShortName(definition.short_name));
+        }
+        let division = self.virtual_divisions.register(definition)?;
+        self.virtual_divisions.save();
+        self.all_divs.retain(|div| div.name != division.name);
+        self.all_divs.push(division.clone());
+        self.cycle_settings
+            .order
+            .retain(|div| div.name != division.name);
+        self.cycle_settings.order.push(division.clone());
+        Ok(division)
+    }
+
+    pub fn remove_virtual_division(&mut self, name: &str) -> Result<(), Error> {
+        let division = self
+            .virtual_divisions
+            .remove(name)
+            .ok_or(Error::InvalidDivision(name.to_string()))?;
+        self.virtual_divisions.save();
+        self.all_divs.retain(|div| div != &division);
+        self.cycle_settings.order.retain(|div| div != &division);
+        self.cycle_settings.include(&division);
+        if self.leaderboard_division == division {
+            self.leaderboard_division = self.all_divs.first().unwrap().clone();
+        }
+        Ok(())
+    }
+
     pub fn current_leaderboard_state(&self) -> LeaderboardState {
         let current_players = self.available_players().into_iter().cloned().collect_vec();
         let previous = self
@@ -555,10 +708,177 @@ impl FlipUpVMixCoordinator {
         }
     }
 
+    /// Remembers the current standings so that highlights are only made for what happens from now on
+    pub fn seed_highlights(&mut self) {
+        self.add_state_to_leaderboard();
+        let players = self.handler.get_players();
+        self.highlights
+            .seed(&players, &self.leaderboard, &self.all_divs, self.round_ind);
+    }
+
+    /// Expects the leaderboard to already contain the changed scores
+    pub fn detect_highlights(&mut self, changes: &[ScoreChange]) -> Vec<dto::Highlight> {
+        let players = self.handler.get_players();
+        self.highlights
+            .detect(changes, &players, &self.leaderboard, self.round_ind)
+    }
+
+    pub fn highlights(&self, since: usize) -> Vec<dto::Highlight> {
+        self.highlights.since(since).to_vec()
+    }
+
+    pub fn mark_replays(&mut self, highlights: &[dto::Highlight]) {
+        self.replay.mark_highlights(highlights, &self.vmix_queue);
+    }
+
+    /// Tagged with the focused player and the hole they are on, unless others are given
+    pub fn mark_replay(
+        &mut self,
+        player_name: Option<String>,
+        hole: Option<u8>,
+        label: String,
+        pre_roll: Option<u32>,
+    ) -> dto::ReplayEvent {
+        let (player_name, hole) = self.replay_tag(player_name, hole);
+        self.replay.mark(
+            Some(player_name),
+            Some(hole),
+            label,
+            pre_roll,
+            &self.vmix_queue,
+        )
+    }
+
+    /// Ends the event started by [`ReplayMarker::mark_in`], tagged like [`Self::mark_replay`]
+    pub fn mark_replay_out(
+        &mut self,
+        player_name: Option<String>,
+        hole: Option<u8>,
+        label: String,
+    ) -> dto::ReplayEvent {
+        let (player_name, hole) = self.replay_tag(player_name, hole);
+        self.replay
+            .mark_out(Some(player_name), Some(hole), label, &self.vmix_queue)
+    }
+
+    fn replay_tag(&self, player_name: Option<String>, hole: Option<u8>) -> (String, u8) {
+        (
+            player_name.unwrap_or(self.focused_player().name.clone()),
+            hole.unwrap_or(self.current_hole() as u8 + 1),
+        )
+    }
+
+    /// Stores the verified results of the current round in the hole history
+    pub fn record_hole_history(&mut self) {
+        let players = self.handler.get_players();
+        self.hole_history.record(&self.event_ids, &players);
+    }
+
+    /// History of the hole on the layout of the focused player.
+    /// Uses the leaderboard division if no division is given
+    pub fn hole_history(
+        &self,
+        hole: u8,
+        division: Option<&str>,
+    ) -> Result<dto::HoleHistory, Error> {
+        if hole == 0 || hole > 18 {
+            return Err(Error::TooManyHoles);
+        }
+        let division = match division {
+            Some(division) => self
+                .find_division_by_name(division)
+                .ok_or(Error::InvalidDivision(division.to_string()))?,
+            None => self.leaderboard_division.clone(),
+        };
+        let player = self.focused_player();
+        let layout = player.layout.key();
+        Ok(self.hole_history.to_dto(
+            &layout,
+            hole,
+            &division,
+            &self.event_ids[player.event_number],
+        ))
+    }
+
+    /// Course metadata of the hole on the layout of the focused player
+    pub fn hole_metadata(&self, hole: u8) -> Result<dto::HoleMetadata, Error> {
+        self.focused_player()
+            .holes
+            .find_hole(hole)
+            .map(|hole| hole.metadata.clone())
+            .ok_or(Error::TooManyHoles)
+    }
+
+    pub fn set_event_name(&mut self, event_id: &str, name: String) {
+        self.hole_history.set_event_name(event_id, name)
+    }
+
+    /// Club table of the division for the round shown on the leaderboard
+    pub fn club_standings(
+        &mut self,
+        division: &str,
+        counting: usize,
+    ) -> Result<ClubStandings, Error> {
+        if counting == 0 {
+            return Err(Error::InvalidCountingScores(counting));
+        }
+        let division = self
+            .find_division_by_name(division)
+            .ok_or(Error::InvalidDivision(division.to_string()))?;
+        self.add_state_to_leaderboard();
+        let state = self
+            .leaderboard
+            .state(self.leaderboard_round)
+            .ok_or(Error::RoundNotInitialised)?;
+        Ok(ClubStandings::new(state, &division, &self.clubs, counting))
+    }
+
+    pub fn show_club_standings(&mut self, division: &str, counting: usize) -> Result<(), Error> {
+        title_mapping::get().require_input(ClubStandingsProperty::GRAPHIC_NAME)?;
+        let standings = self.club_standings(division, counting)?;
+        self.queue_add(&standings.to_vmix_instructions());
+        Ok(())
+    }
+
+    /// Round is 0-indexed
+    pub fn round_summary(&mut self, division: &str, round: usize) -> Result<RoundSummary, Error> {
+        let division = self
+            .find_division_by_name(division)
+            .ok_or(Error::InvalidDivision(division.to_string()))?;
+        self.add_state_to_leaderboard();
+        let state = self
+            .leaderboard
+            .state(round)
+            .filter(|state| state.round() == round)
+            .ok_or(Error::RoundNotInitialised)?;
+        Ok(RoundSummary::new(state, division))
+    }
+
+    pub fn show_round_summary(&mut self, division: &str, round: usize) -> Result<(), Error> {
+        title_mapping::get().require_input(RoundSummaryProperty::GRAPHIC_NAME)?;
+        let summary = self.round_summary(division, round)?;
+        self.queue_add(&summary.to_vmix_instructions());
+        Ok(())
+    }
+
     pub fn set_leaderboard(&mut self, lb_start_ind: Option<usize>) {
         if self.current_hole() <= 18 {
             self.add_state_to_leaderboard();
-            self.queue_add(&FlipUpVMixCoordinator::clear_lb(10));
+            // Only rows the page does not fill are cleared, so shown rows do not flicker
+            let shown = self
+                .leaderboard
+                .page_funcs(&self.leaderboard_division, self.leaderboard_round)
+                .iter()
+                .filter_map(VMixInterfacer::state_key)
+                .collect::<HashSet<_>>();
+            let clear = FlipUpVMixCoordinator::clear_lb(self.leaderboard.page_size)
+                .into_iter()
+                .filter(|func| {
+                    func.state_key()
+                        .is_some_and(|field| !shown.contains(&field))
+                })
+                .collect_vec();
+            self.queue_add(&clear);
             self.leaderboard.send_to_vmix(
                 &self.leaderboard_division,
                 self.vmix_queue.clone(),
@@ -571,14 +891,15 @@ impl FlipUpVMixCoordinator {
     }
 
     pub fn set_to_hole(&mut self, hole: usize) -> Result<(), Error> {
+        let theme = self.themes.active();
         let player = self.focused_player_mut();
         let mut player_interfaces = vec![];
         let mut current_player_interfaces = vec![];
         // Previously had shift-scores here
         for x in 1..=hole {
             player.hole_shown_up_until = x;
-            let all_values = player.increase_score()?;
-            let current = player.set_all_current_player_values(&all_values);
+            let all_values = player.increase_score(&theme)?;
+            let current = player.set_all_current_player_values(&theme, &all_values);
             player_interfaces.extend(all_values);
             current_player_interfaces.extend(current);
         }
@@ -595,6 +916,7 @@ impl FlipUpVMixCoordinator {
         hole_update: &GeneralChannel<HoleUpdate>,
     ) -> Result<(), Error> {
         let broadcast_type = self.broadcast_type.clone();
+        let theme = self.themes.active();
         let player = self.focused_player_mut();
 
         dbg!(&player.name);
@@ -604,12 +926,12 @@ impl FlipUpVMixCoordinator {
         };
 
         if throws_condition && player.hole_shown_up_until <= 17 {
-            let mut f = player.increase_score()?;
+            let mut f = player.increase_score(&theme)?;
             self.add_state_to_leaderboard();
             let player = self.focused_player();
             let lb_things = player.add_lb_things(&self.leaderboard);
 
-            let mut current = player.set_all_current_player_values(&f);
+            let mut current = player.set_all_current_player_values(&theme, &f);
             let more_current = lb_things
                 .iter()
                 .flat_map(|interface| interface.to_owned().into_current_player())
@@ -646,33 +968,38 @@ impl FlipUpVMixCoordinator {
     }
 
     pub fn revert_score(&mut self) {
-        let f = self.focused_player_mut().revert_hole_score();
+        let theme = self.themes.active();
+        let f = self.focused_player_mut().revert_hole_score(&theme);
         self.queue_add(&f);
     }
     pub fn reset_score(&mut self) {
         self.current_through = 0;
+        let theme = self.themes.active();
 
         match self.broadcast_type.as_ref() {
             BroadcastType::Live => {
-                let f = self.focused_player_mut().reset_scores();
+                let f = self.focused_player_mut().reset_scores(&theme);
                 self.queue_add(&f)
             }
-            BroadcastType::PostLive => self.vmix_function_on_card_mut(&Player::reset_scores),
+            BroadcastType::PostLive => {
+                self.vmix_function_on_card_mut(&|player| player.reset_scores(&theme))
+            }
         }
     }
 
     pub fn reset_scores(&mut self) {
         let return_vec: Vec<VMixInterfacer<VMixPlayerInfo>> = vec![];
-        let actions = self.focused_player_mut().reset_scores();
+        let theme = self.themes.active();
+        let actions = self.focused_player_mut().reset_scores(&theme);
         self.queue_add(&actions);
-        self.queue_add(&FlipUpVMixCoordinator::clear_lb(10));
+        self.queue_add(&FlipUpVMixCoordinator::clear_lb(self.leaderboard.page_size));
         self.queue_add(&return_vec);
     }
 
     pub fn get_div_names(&self) -> Vec<String> {
         let mut return_vec = vec![];
 
-        for div in self.handler.clone().get_divisions() {
+        for div in &self.all_divs {
             return_vec.push(div.name.clone());
         }
         return_vec
@@ -695,7 +1022,11 @@ impl FlipUpVMixCoordinator {
     pub fn increase_throw(&mut self) {
         self.focused_player_mut().throws += 1;
         let f = [self.focused_player_mut().set_throw()];
-        self.queue_add(&self.focused_player().set_all_current_player_values(&f));
+        self.queue_add(
+            &self
+                .focused_player()
+                .set_all_current_player_values(&self.themes.active(), &f),
+        );
         self.queue_add(&f)
     }
 
@@ -703,7 +1034,11 @@ impl FlipUpVMixCoordinator {
         if self.focused_player().throws != 0 {
             self.focused_player_mut().throws -= 1;
             let f = &[self.focused_player_mut().set_throw()];
-            self.queue_add(&self.focused_player().set_all_current_player_values(f));
+            self.queue_add(
+                &self
+                    .focused_player()
+                    .set_all_current_player_values(&self.themes.active(), f),
+            );
             self.queue_add(f);
         }
     }
@@ -7,19 +7,23 @@ use rayon::prelude::*;
 use hole::VMixHoleInfo;
 
 use crate::api::Error;
+use crate::assets;
+use crate::controller;
 use crate::controller::coordinator::BroadcastType;
-use crate::controller::get_data::{HoleResult, DEFAULT_FOREGROUND_COL_ALPHA};
+use crate::controller::get_data::HoleResult;
 use crate::controller::hole::{DroneHoleInfo, HoleDifficulty, HoleStats};
+use crate::controller::player_image;
 use crate::controller::queries::layout::hole::Hole;
 use crate::controller::queries::layout::{Holes, Layout};
 use crate::controller::queries::results_getter::PlayerResults;
 use crate::controller::queries::Division;
+use crate::controller::stats::{PlayerStat, StatCounts, StatScope};
 use crate::controller::{hole, queries};
+use crate::dto;
 use crate::flipup_vmix_controls::{
     Image, LeaderBoardProperty, Leaderboard, LeaderboardMovement, OverarchingScore, Score,
 };
 use crate::vmix::functions::{Compare2x2, CurrentPlayer, VMixInterfacer, VMixPlayerInfo};
-use crate::{controller, util};
 
 // TODO: Refactor out
 #[derive(Debug, Clone, Default)]
@@ -110,9 +114,9 @@ impl PlayerRound {
         hole_stats: Vec<HoleStats>,
         holes: &Holes,
         division: &Division,
-    ) -> Vec<VMixInterfacer<VMixHoleInfo>> {
+    ) -> Result<Vec<VMixInterfacer<VMixHoleInfo>>, Error> {
         let mut r_vec: Vec<VMixInterfacer<VMixHoleInfo>> = vec![];
-        let hole = holes.find_hole(hole).unwrap();
+        let hole = holes.find_hole(hole).ok_or(Error::TooManyHoles)?;
 
         r_vec.push(VMixInterfacer::set_only_input(VMixHoleInfo::Hole(
             hole.hole,
@@ -135,13 +139,27 @@ impl PlayerRound {
             r_vec.push(VMixInterfacer::set_only_input(
                 VMixHoleInfo::AverageResult { score: avg, cmp },
             ));
+            let distribution = stat.distribution(division);
+            if distribution.total > 0 {
+                r_vec.extend(
+                    distribution
+                        .vmix_info()
+                        .into_iter()
+                        .map(VMixInterfacer::set_only_input),
+                );
+            }
             r_vec.push(VMixInterfacer::set_only_input(VMixHoleInfo::Difficulty {
                 difficulty: HoleDifficulty::new(hole_stats, division),
                 hole: hole.hole as usize,
             }));
         }
 
-        if division.name == "Mixed Pro Open" {
+        let metadata = &hole.metadata;
+        if let Some(elevation) = metadata.elevation {
+            r_vec.push(VMixInterfacer::set_only_input(VMixHoleInfo::Elevation(
+                elevation,
+            )))
+        } else if division.name == "Mixed Pro Open" {
             r_vec.push(VMixInterfacer::set_only_input(VMixHoleInfo::Elevation(
                 [
                     -3, 10, -8, -4, 1, -10, 8, 11, -4, 3, 1, 1, -1, -6, 8, -12, 4, -6,
@@ -154,12 +172,23 @@ impl PlayerRound {
                 ][(hole.hole - 1) as usize],
             )))
         }
-        r_vec
+        r_vec.extend(
+            [
+                metadata.name.clone().map(VMixHoleInfo::HoleName),
+                metadata.tee.clone().map(VMixHoleInfo::TeeDescription),
+                metadata.basket.clone().map(VMixHoleInfo::BasketDescription),
+            ]
+            .into_iter()
+            .flatten()
+            .map(VMixInterfacer::set_only_input),
+        );
+        Ok(r_vec)
     }
 
     pub fn get_drone_info(
         &self,
         hole: u8,
+        holes: &Holes,
         funcs: &[VMixInterfacer<VMixHoleInfo>],
         division: &Division,
     ) -> Vec<VMixInterfacer<DroneHoleInfo>> {
@@ -173,13 +202,11 @@ impl PlayerRound {
         } else {
             "fpo"
         };
-        funcs.push(VMixInterfacer::set_image(
-            format!(
-                "C:\\livegrafik-flipup\\holemaps\\{}hole{}.png",
-                division_name, hole
-            ),
-            DroneHoleInfo::HoleMap,
-        ));
+        let hole_map = holes
+            .find_hole(hole)
+            .and_then(|hole| hole.metadata.hole_map.clone())
+            .unwrap_or_else(|| assets::get().hole_map(&format!("{division_name}hole{hole}.png")));
+        funcs.push(VMixInterfacer::set_image(hole_map, DroneHoleInfo::HoleMap));
         funcs
     }
 
@@ -228,6 +255,33 @@ impl PlayerRound {
         self.holes_sorted_by_completion().last()
     }
 
+    pub fn stats(&self) -> StatCounts {
+        StatCounts::new(
+            self.results
+                .iter()
+                .filter_map(|result| result.tjing_result.as_ref()),
+        )
+    }
+
+    pub fn verified_results(&self) -> impl Iterator<Item = &queries::results_getter::HoleResult> {
+        self.results
+            .iter()
+            .filter_map(|result| result.tjing_result.as_ref())
+            .filter(|result| result.is_verified)
+    }
+
+    /// Amount of holes in a row under par, counting back from the latest finished hole
+    pub fn under_par_streak(&self) -> usize {
+        self.holes_sorted_by_completion()
+            .rev()
+            .take_while(|result| result.actual_score() < 0)
+            .count()
+    }
+
+    pub fn is_bogey_free(&self) -> bool {
+        self.results.iter().all(|result| result.actual_score() <= 0)
+    }
+
     pub fn the_latest_6_holes(&self, take_amount: usize) -> Vec<Option<&HoleResult>> {
         let mut results = self
             .holes_sorted_by_completion()
@@ -272,13 +326,13 @@ pub struct Player {
     pub first_scored: bool,
     pub visible_player: bool,
     pub division: Arc<Division>,
-    image_location: Option<String>,
     pub holes: Holes,
     pub event_number: usize,
     broadcast_type: Arc<BroadcastType>,
     pub layout: Arc<Layout>,
     pub start_time: chrono::NaiveTime,
     pub group_id: String,
+    pub previous_rounds_stats: StatCounts,
 }
 
 impl Player {
@@ -326,23 +380,6 @@ impl Player {
 
         let results = PlayerRound::new(results, round, starts_at_hole);
 
-        let image_location = image_id.clone().map(|image| {
-            if cfg!(target_os = "windows") {
-                format!(
-                    "C:\\livegrafik-flipup\\_conf\\images\\{}.png",
-                    player.id.clone().into_inner()
-                )
-            } else {
-                format!("images/{}.png", player.id.clone().into_inner())
-            }
-        });
-        if let Some(image) = image_id.to_owned() {
-            let img = image_location.clone().unwrap();
-            std::thread::spawn(|| {
-                let _ = util::download_image_to_file(image, img);
-            });
-        }
-
         Ok(Self {
             player_id: player.id.into_inner(),
             image_url: image_id,
@@ -358,7 +395,6 @@ impl Player {
             dns: player.dns.is_dns,
             round_ind: round,
             division,
-            image_location,
             holes,
             event_number,
             broadcast_type,
@@ -369,6 +405,14 @@ impl Player {
         })
     }
 
+    pub fn image_request(&self) -> player_image::ImageRequest {
+        player_image::ImageRequest {
+            player_id: self.player_id.clone(),
+            pdga_number: self.pdga_num,
+            url: self.image_url.clone(),
+        }
+    }
+
     pub fn null_player() -> Self {
         Player {
             player_id: "".to_string(),
@@ -486,6 +530,7 @@ impl Player {
         lb: &Leaderboard,
         max_all: bool,
     ) -> Result<Vec<VMixInterfacer<VMixPlayerInfo>>, Error> {
+        let theme = lb.theme();
         let mut return_vec = vec![];
         return_vec.extend(self.set_name());
         if let Some(set_pos) = self.set_pos(lb) {
@@ -503,17 +548,17 @@ impl Player {
         }
         if max_all {
             for result in &self.results.results {
-                return_vec.extend(result.to_score().update_score(self.vmix_index()))
+                return_vec.extend(result.to_score().update_score(&theme, self.vmix_index()))
             }
         } else if self.hole_shown_up_until != 0 {
             let funcs: Vec<_> = (0..self.hole_shown_up_until)
                 .into_par_iter()
                 .flat_map(|hole| self.get_score(hole))
-                .flat_map(|score| score.update_score(self.vmix_index()))
+                .flat_map(|score| score.update_score(&theme, self.vmix_index()))
                 .collect();
             return_vec.extend(funcs);
         }
-        return_vec.extend(self.delete_all_scores_after_current(max_all));
+        return_vec.extend(self.delete_all_scores_after_current(&theme, max_all));
 
         return_vec.push(self.set_throw());
         return_vec.extend(self.add_lb_things(lb));
@@ -533,13 +578,14 @@ impl Player {
             .map(|val| val.into_compare_2x2_player(index))
             .collect();
 
-        let img = if cfg!(target_os = "windows") {
-            self.image_location.clone()
-        } else {
-            self.image_url.clone()
+        let img = match &self.image_url {
+            Some(url) if !cfg!(target_os = "windows") => url.clone(),
+            _ => player_image::location(&self.player_id)
+                .to_string_lossy()
+                .into_owned(),
         };
         output.push(VMixInterfacer::set_image(
-            img.unwrap_or("C:\\livegrafik-flipup\\_conf\\placeholder.png".to_string()),
+            img,
             Compare2x2::PlayerImage { index },
         ));
         Ok(output)
@@ -554,6 +600,7 @@ impl Player {
 
     pub fn set_all_current_player_values(
         &self,
+        theme: &dto::Theme,
         interfaces: &[VMixInterfacer<VMixPlayerInfo>],
     ) -> Vec<VMixInterfacer<CurrentPlayer>> {
         let mut second_values: Vec<_> = interfaces
@@ -568,10 +615,12 @@ impl Player {
                 .par_iter()
                 .enumerate()
                 .flat_map(|(hole_index, result)| match result {
-                    Some(res) => res.to_current_player(hole_index + 1, self.vmix_index()),
-                    None => {
-                        HoleResult::hide_current_player_score(hole_index + 1, self.vmix_index())
-                    }
+                    Some(res) => res.to_current_player(theme, hole_index + 1, self.vmix_index()),
+                    None => HoleResult::hide_current_player_score(
+                        theme,
+                        hole_index + 1,
+                        self.vmix_index(),
+                    ),
                 })
                 .collect::<Vec<_>>(),
         );
@@ -592,7 +641,10 @@ impl Player {
         self.total_score += self.round_score
     }
 
-    pub fn increase_score(&mut self) -> Result<Vec<VMixInterfacer<VMixPlayerInfo>>, Error> {
+    pub fn increase_score(
+        &mut self,
+        theme: &dto::Theme,
+    ) -> Result<Vec<VMixInterfacer<VMixPlayerInfo>>, Error> {
         let mut return_vec: Vec<VMixInterfacer<VMixPlayerInfo>> = vec![];
 
         if !self.first_scored {
@@ -628,7 +680,7 @@ impl Player {
 
         let score = self
             .get_current_shown_score()
-            .update_score(self.vmix_index());
+            .update_score(theme, self.vmix_index());
 
         self.round_score += s.par_score() as isize;
         self.total_score += s.par_score() as isize;
@@ -683,11 +735,11 @@ impl Player {
     fn add_round_score(&self, outside_instructions: &mut Vec<VMixInterfacer<VMixPlayerInfo>>) {
         outside_instructions.extend(self.overarching_score_representation().set_round_score())
     }
-    pub fn revert_hole_score(&mut self) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
+    pub fn revert_hole_score(&mut self, theme: &dto::Theme) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
         let mut return_vec = vec![];
         if self.hole_shown_up_until > 0 {
             self.hole_shown_up_until -= 1;
-            return_vec.extend(self.del_current_score());
+            return_vec.extend(self.del_current_score(theme));
             let result = self.results.hole_score(self.hole_shown_up_until);
             self.results.results.pop();
             self.round_score -= result;
@@ -772,7 +824,7 @@ impl Player {
         }
     }*/
 
-    fn del_score(&self, hole: usize) -> [VMixInterfacer<VMixPlayerInfo>; 3] {
+    fn del_score(&self, theme: &dto::Theme, hole: usize) -> [VMixInterfacer<VMixPlayerInfo>; 3] {
         let score_prop = VMixPlayerInfo::Score {
             hole,
             player: self.vmix_index(),
@@ -784,17 +836,18 @@ impl Player {
         };
         [
             VMixInterfacer::set_text("".to_string(), score_prop.clone()),
-            VMixInterfacer::set_color(DEFAULT_FOREGROUND_COL_ALPHA, col_prop),
+            VMixInterfacer::set_color(&theme.hidden(), col_prop),
             VMixInterfacer::set_text_visible_off(score_prop),
         ]
     }
 
-    fn del_current_score(&self) -> [VMixInterfacer<VMixPlayerInfo>; 3] {
-        self.del_score(self.hole_shown_up_until + 1)
+    fn del_current_score(&self, theme: &dto::Theme) -> [VMixInterfacer<VMixPlayerInfo>; 3] {
+        self.del_score(theme, self.hole_shown_up_until + 1)
     }
 
     fn delete_all_scores_after_current(
         &self,
+        theme: &dto::Theme,
         show_max: bool,
     ) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
         if show_max {
@@ -808,19 +861,19 @@ impl Player {
                             .as_ref()
                             .is_some_and(|res| res.is_verified)
                 })
-                .flat_map(|result| self.del_score(result.hole as usize))
+                .flat_map(|result| self.del_score(theme, result.hole as usize))
                 .collect_vec()
         } else {
             ((self.hole_shown_up_until + 1)..=18)
                 .par_bridge()
-                .flat_map(|hole| self.del_score(hole))
+                .flat_map(|hole| self.del_score(theme, hole))
                 .collect()
         }
     }
 
-    pub fn reset_scores(&mut self) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
+    pub fn reset_scores(&mut self, theme: &dto::Theme) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
         let mut return_vec: Vec<VMixInterfacer<VMixPlayerInfo>> = vec![];
-        return_vec.extend(self.delete_all_scores_after_current(false));
+        return_vec.extend(self.delete_all_scores_after_current(theme, false));
         self.hole_shown_up_until = 0;
         self.round_score = 0;
         self.total_score = self.score_before_round();
@@ -845,17 +898,17 @@ impl Player {
     }
 
     fn set_lb_hr(&self) -> VMixInterfacer<LeaderBoardProperty> {
-        let value = if self.hot_round
+        let image = if self.hot_round
             && self.round_ind != 0
             && self.hole_shown_up_until != 0
             && self.hole_shown_up_until < 19
         {
-            r"X:\FLIPUP\grafik\fire.png"
+            Image::Flames
         } else {
-            r"X:\FLIPUP\grafik\alpha.png"
+            Image::Nothing
         };
         VMixInterfacer::set_image(
-            value.to_string(),
+            image.to_hot_round_location(),
             LeaderBoardProperty::HotRound(self.position).into(),
         )
     }
@@ -917,39 +970,31 @@ impl Player {
                 "".to_string(),
                 LeaderBoardProperty::TotalScore { pos: self.position },
             ),
+            VMixInterfacer::set_text(
+                "".to_string(),
+                LeaderBoardProperty::DivisionBadge(self.position),
+            ),
         ];
         return_vec.extend(self.set_moves());
         return_vec.push(self.set_thru());
         return_vec
     }
 
-    fn set_stats(&self) -> [VMixInterfacer<VMixPlayerInfo>; 2] {
-        let results = self
-            .results
-            .results
-            .iter()
-            .filter(|res| res.tjing_result.as_ref().is_some_and(|res| res.is_verified))
-            .map(|result| result.tjing_result.as_ref().unwrap())
-            .collect_vec();
-
-        let finished_holes = results.len() as f64;
+    pub fn stats(&self, scope: StatScope) -> StatCounts {
+        match scope {
+            StatScope::Round => self.results.stats(),
+            StatScope::Event => self.results.stats() + self.previous_rounds_stats,
+            StatScope::PreviousRounds => self.previous_rounds_stats,
+        }
+    }
 
-        let (circle_hit, inside_putt) = if finished_holes > 0. {
-            let circle_hit_count = results
-                .par_iter()
-                .filter(|result| result.is_circle_hit)
-                .count() as f64;
-            let circle_hit = (circle_hit_count / finished_holes * 100.).round() as usize;
-            let inside_putt_count = results
-                .par_iter()
-                .filter(|result| result.is_inside_putt)
-                .count() as f64;
-            let inside_putt = (inside_putt_count / finished_holes * 100.).round() as usize;
-            (circle_hit, inside_putt)
-        } else {
-            (0, 0)
-        };
-        [
+    fn set_stats(&self) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
+        let round = self.stats(StatScope::Round);
+        let circle_hit = round
+            .percentage(PlayerStat::C1Regulation)
+            .unwrap_or_default();
+        let inside_putt = round.inside_putt_percentage().unwrap_or_default();
+        let mut instructions = vec![
             VMixInterfacer::set_text(
                 format!("{circle_hit}%"),
                 VMixPlayerInfo::CircleHit(self.vmix_index()),
@@ -958,7 +1003,14 @@ impl Player {
                 format!("{inside_putt}%"),
                 VMixPlayerInfo::InsidePutt(self.vmix_index()),
             ),
-        ]
+        ];
+        for scope in StatScope::ALL {
+            instructions.extend(
+                self.stats(scope)
+                    .to_vmix_instructions(self.vmix_index(), scope),
+            );
+        }
+        instructions
     }
 }
 
@@ -987,11 +1039,11 @@ impl RankUpDown {
 
     fn make_arrow(&self, pos: usize) -> VMixInterfacer<LeaderBoardProperty> {
         let img = match self {
-            RankUpDown::Up(_) => r"C:\livegrafik-flipup\greentri.png",
-            RankUpDown::Down(_) => r"C:\livegrafik-flipup\redtri.png",
-            RankUpDown::Same => r"C:\livegrafik-flipup\alpha.png",
+            RankUpDown::Up(_) => Image::GreenTriUp,
+            RankUpDown::Down(_) => Image::RedTriDown,
+            RankUpDown::Same => Image::Nothing,
         }
-        .to_string();
+        .to_arrow_location();
 
         VMixInterfacer::set_image(img, LeaderBoardProperty::Arrow { pos }.into())
     }
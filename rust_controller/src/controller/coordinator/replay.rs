@@ -0,0 +1,142 @@
+//! Marks replay events in vMix Replay for notable shots, so the replay operator has them ready.
+//!
+//! Every event is named after the player, hole and what happened. Route the `ReplayMark` graphic
+//! to the replay machine if it is not the main vMix.
+//!
+//! Highlights are only known when the score is entered, not when the shot is played, so their
+//! events start [`dto::ReplaySettings::automatic_pre_roll`] back and only point the operator near the shot.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::dto;
+use crate::json_file::{load_json_file, save_json_file};
+use crate::vmix::functions::{ReplayMark, VMixInterfacer};
+use crate::vmix::VMixQueue;
+
+pub const REPLAY_FILE: &str = "replay.json";
+/// Only the newest events are kept, vMix Replay has all of them
+const MAX_EVENTS: usize = 500;
+
+#[derive(Debug, Clone, Default)]
+pub struct ReplayMarker {
+    settings: dto::ReplaySettings,
+    events: VecDeque<dto::ReplayEvent>,
+}
+
+impl ReplayMarker {
+    pub fn load() -> Self {
+        Self {
+            settings: load_json_file(REPLAY_FILE),
+            ..Default::default()
+        }
+    }
+
+    pub fn settings(&self) -> &dto::ReplaySettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: dto::ReplaySettings) {
+        self.settings = settings;
+        save_json_file(REPLAY_FILE, &self.settings);
+    }
+
+    /// Oldest first
+    pub fn events(&self) -> Vec<dto::ReplayEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    /// Marks the highlights of the kinds in the settings, with the automatic pre-roll
+    pub fn mark_highlights(&mut self, highlights: &[dto::Highlight], queue: &Arc<VMixQueue>) {
+        for highlight in highlights {
+            let kind = highlight.kind.name();
+            if self.settings.kinds.iter().any(|marked| marked == kind) {
+                self.mark(
+                    Some(highlight.player_name.clone()),
+                    highlight.hole,
+                    kind.to_string(),
+                    Some(self.settings.automatic_pre_roll),
+                    queue,
+                );
+            }
+        }
+    }
+
+    /// Marks the last `pre_roll` seconds, or the pre-roll of the settings
+    pub fn mark(
+        &mut self,
+        player_name: Option<String>,
+        hole: Option<u8>,
+        label: String,
+        pre_roll: Option<u32>,
+        queue: &Arc<VMixQueue>,
+    ) -> dto::ReplayEvent {
+        let pre_roll = pre_roll.unwrap_or(self.settings.pre_roll);
+        self.add_event(
+            VMixInterfacer::replay_mark_in_out(pre_roll),
+            player_name,
+            hole,
+            label,
+            pre_roll,
+            queue,
+        )
+    }
+
+    /// Starts an event at the live position, it is added when [`Self::mark_out`] ends it
+    pub fn mark_in(&self, queue: &Arc<VMixQueue>) {
+        queue.add(std::iter::once(
+            VMixInterfacer::<ReplayMark>::replay_mark_in(),
+        ));
+    }
+
+    pub fn mark_out(
+        &mut self,
+        player_name: Option<String>,
+        hole: Option<u8>,
+        label: String,
+        queue: &Arc<VMixQueue>,
+    ) -> dto::ReplayEvent {
+        self.add_event(
+            VMixInterfacer::replay_mark_out(),
+            player_name,
+            hole,
+            label,
+            0,
+            queue,
+        )
+    }
+
+    fn add_event(
+        &mut self,
+        mark: VMixInterfacer<ReplayMark>,
+        player_name: Option<String>,
+        hole: Option<u8>,
+        label: String,
+        pre_roll: u32,
+        queue: &Arc<VMixQueue>,
+    ) -> dto::ReplayEvent {
+        let text = [
+            player_name.clone(),
+            hole.map(|hole| format!("H{hole}")),
+            Some(label.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+        queue.add([mark, VMixInterfacer::replay_event_text(text.clone())].into_iter());
+        let event = dto::ReplayEvent {
+            timestamp: chrono::Utc::now().timestamp(),
+            player_name,
+            hole,
+            label,
+            text,
+            pre_roll,
+        };
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event.clone());
+        event
+    }
+}
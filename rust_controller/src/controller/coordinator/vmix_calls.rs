@@ -4,26 +4,27 @@ use std::sync::Arc;
 use itertools::Itertools;
 
 use crate::api::Error;
-use crate::controller::coordinator::FlipUpVMixCoordinator;
-use crate::controller::hole::HoleStats;
+use crate::controller::coordinator::{BroadcastType, FlipUpVMixCoordinator};
+use crate::controller::hole::{HoleStats, VMixHoleInfo};
 use crate::controller::queries;
 use crate::controller::queries::Division;
+use crate::flipup_vmix_controls::LeaderBoardProperty;
+use crate::vmix::functions::{
+    CurrentPlayer, VMixFunction, VMixInterfacer, VMixPlayerInfo, VMixSelectionTrait,
+};
 
 impl FlipUpVMixCoordinator {
     pub fn make_hole_info(&mut self, hole: Option<usize>) {
         self.set_current_through();
         let hole = hole.unwrap_or(self.current_hole());
-        if hole <= 18 {
-            let current_hole = hole as u8 + 1;
-            let stats = self.make_stats();
+        let Ok(current_hole) = u8::try_from(hole + 1) else {
+            return;
+        };
+        if let Ok(result) = self.hole_info(current_hole) {
             let holes = self.focused_player().holes.clone();
-            let div: &Division = &self.leaderboard_division.clone();
-            let result =
-                self.focused_player_mut()
-                    .results
-                    .get_hole_info(current_hole, stats, &holes, div);
             let drone_result = self.focused_player().results.get_drone_info(
                 current_hole,
+                &holes,
                 &result,
                 &self.leaderboard_division,
             );
@@ -32,6 +33,90 @@ impl FlipUpVMixCoordinator {
         }
     }
 
+    /// Hole info of the focused player's layout, `hole` starts at 1
+    pub fn hole_info(&mut self, hole: u8) -> Result<Vec<VMixInterfacer<VMixHoleInfo>>, Error> {
+        let stats = self.make_stats();
+        let holes = self.focused_player().holes.clone();
+        let div: &Division = &self.leaderboard_division.clone();
+        let mut result = self
+            .focused_player_mut()
+            .results
+            .get_hole_info(hole, stats, &holes, div)?;
+        let player = self.focused_player();
+        result.extend(
+            self.hole_history
+                .vmix_info(
+                    &player.layout.key(),
+                    hole,
+                    div,
+                    &self.event_ids[player.event_number],
+                )
+                .into_iter()
+                .map(VMixInterfacer::set_only_input),
+        );
+        Ok(result)
+    }
+
+    /// The current leaderboard page of the division, or of the leaderboard division
+    pub fn leaderboard_funcs(
+        &mut self,
+        division: Option<&str>,
+    ) -> Result<Vec<VMixInterfacer<LeaderBoardProperty>>, Error> {
+        let division = match division {
+            Some(name) => self
+                .find_division_by_name(name)
+                .ok_or(Error::InvalidDivision(name.to_string()))?,
+            None => self.leaderboard_division.clone(),
+        };
+        self.add_state_to_leaderboard();
+        Ok(self
+            .leaderboard
+            .page_funcs(&division, self.leaderboard_round))
+    }
+
+    /// Every value of every player in the focused card
+    pub fn card_funcs(&mut self) -> Result<Vec<VMixInterfacer<VMixPlayerInfo>>, Error> {
+        self.add_state_to_leaderboard();
+        let live = *self.broadcast_type == BroadcastType::Live;
+        let funcs = self
+            .current_players()
+            .into_iter()
+            .map(|player| player.set_all_values(&self.leaderboard, live))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(funcs.into_iter().flatten().collect())
+    }
+
+    pub fn focused_player_funcs(&mut self) -> Result<Vec<VMixInterfacer<CurrentPlayer>>, Error> {
+        self.add_state_to_leaderboard();
+        let live = *self.broadcast_type == BroadcastType::Live;
+        let player = self.focused_player();
+        let values = player.set_all_values(&self.leaderboard, live)?;
+        Ok(player.set_all_current_player_values(&self.themes.active(), &values))
+    }
+
+    /// Sends every colour again, after the theme has changed
+    pub fn resend_colours(&mut self) -> Result<(), Error> {
+        fn is_colour<T: VMixSelectionTrait>(func: &&VMixInterfacer<T>) -> bool {
+            matches!(func.function, VMixFunction::SetColor)
+        }
+        let card = self.card_funcs()?;
+        self.queue_add(card.iter().filter(is_colour));
+        let focused = self.focused_player_funcs()?;
+        self.queue_add(focused.iter().filter(is_colour));
+        let leaderboard = self.leaderboard_funcs(None)?;
+        self.queue_add(leaderboard.iter().filter(is_colour));
+        self.leaderboard
+            .update_little_lb(&self.leaderboard_division, self.vmix_queue.clone());
+
+        let available = self.available_players();
+        if !self.featured_card.card(available.clone()).is_empty()
+            && self.featured_card.player(available).is_some()
+        {
+            self.update_featured_card()?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn make_stats(&self) -> Vec<HoleStats> {
         let mut hole_stats: HashMap<
             usize,
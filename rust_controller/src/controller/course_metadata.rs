@@ -0,0 +1,53 @@
+//! Hole information Tjing does not have, read from one JSON file per layout.
+//!
+//! The file is `course_metadata/<course> - <layout>.json`, characters that can not be in a file
+//! name replaced with `_`:
+//!
+//! ```json
+//! { "holes": [{ "hole": 1, "elevation": -3, "name": "The Drop", "notes": "Wind from the left" }] }
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::controller::queries::layout::Layout;
+use crate::dto::HoleMetadata;
+use crate::json_file::load_json_file;
+
+pub const COURSE_METADATA_DIR: &str = "course_metadata";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CourseMetadata {
+    holes: Vec<HoleMetadata>,
+}
+
+impl CourseMetadata {
+    pub fn path(layout: &Layout) -> PathBuf {
+        let file_name: String = layout
+            .key()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        PathBuf::from(COURSE_METADATA_DIR).join(format!("{file_name}.json"))
+    }
+
+    /// Empty if the layout has no metadata file
+    pub fn load(layout: &Layout) -> Self {
+        load_json_file(Self::path(layout))
+    }
+
+    pub fn into_holes(self) -> HashMap<u8, HoleMetadata> {
+        self.holes
+            .into_iter()
+            .map(|hole| (hole.hole, hole))
+            .collect()
+    }
+}
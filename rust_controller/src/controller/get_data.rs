@@ -11,7 +11,9 @@ use super::queries;
 use crate::api::Error;
 use crate::controller::coordinator::player::{Player, PlayerRound};
 use crate::controller::coordinator::BroadcastType;
+use crate::controller::course_metadata::CourseMetadata;
 use crate::controller::hole::{HoleStats, VMixHoleInfo};
+use crate::controller::player_image;
 use crate::controller::queries::layout::hole::Hole;
 use crate::controller::queries::layout::Holes;
 use crate::controller::queries::Division;
@@ -22,10 +24,6 @@ use crate::flipup_vmix_controls::{OverarchingScore, Score};
 use crate::vmix::functions::*;
 use crate::{controller, dto};
 
-pub const DEFAULT_FOREGROUND_COL: &str = "3F334D";
-pub const DEFAULT_FOREGROUND_COL_ALPHA: &str = "3F334D00";
-pub const DEFAULT_BACKGROUND_COL: &str = "574B60";
-
 #[derive(Debug, Clone)]
 pub struct HoleResult {
     pub hole: u8,
@@ -95,8 +93,12 @@ impl HoleResult {
         self.into()
     }
 
-    pub fn get_score_colour(&self, player: usize) -> VMixInterfacer<VMixPlayerInfo> {
-        self.to_score().update_score_colour(player)
+    pub fn get_score_colour(
+        &self,
+        theme: &dto::Theme,
+        player: usize,
+    ) -> VMixInterfacer<VMixPlayerInfo> {
+        self.to_score().update_score_colour(theme, player)
     }
 
     pub fn get_mov(&self, player: usize) -> [VMixInterfacer<VMixPlayerInfo>; 2] {
@@ -105,6 +107,7 @@ impl HoleResult {
 
     pub fn to_leaderboard_top_6(
         &self,
+        theme: &dto::Theme,
         pos: usize,
         hole: usize,
     ) -> Vec<VMixInterfacer<LeaderboardTop6>> {
@@ -114,7 +117,7 @@ impl HoleResult {
                 LeaderboardTop6::LastScore { pos, hole },
             ),
             VMixInterfacer::set_color(
-                self.to_score().get_score_colour(),
+                &self.to_score().get_score_colour(theme),
                 LeaderboardTop6::LastScoreColour { pos, hole },
             ),
         ]
@@ -122,6 +125,7 @@ impl HoleResult {
 
     pub fn to_current_player(
         &self,
+        theme: &dto::Theme,
         hole: usize,
         player: usize,
     ) -> Vec<VMixInterfacer<CurrentPlayer>> {
@@ -131,13 +135,14 @@ impl HoleResult {
                 CurrentPlayer(VMixPlayerInfo::Score { player, hole }),
             ),
             VMixInterfacer::set_color(
-                self.to_score().get_score_colour(),
+                &self.to_score().get_score_colour(theme),
                 CurrentPlayer(VMixPlayerInfo::ScoreColor { player, hole }),
             ),
         ]
     }
 
     pub fn hide_current_player_score(
+        theme: &dto::Theme,
         hole: usize,
         player: usize,
     ) -> Vec<VMixInterfacer<CurrentPlayer>> {
@@ -147,20 +152,24 @@ impl HoleResult {
                 CurrentPlayer(VMixPlayerInfo::Score { hole, player }),
             ),
             VMixInterfacer::set_color(
-                DEFAULT_FOREGROUND_COL_ALPHA,
+                &theme.hidden(),
                 CurrentPlayer(VMixPlayerInfo::ScoreColor { hole, player }),
             ),
         ]
     }
 
-    pub fn hide_hole_top_6(player: usize, hole: usize) -> Vec<VMixInterfacer<LeaderboardTop6>> {
+    pub fn hide_hole_top_6(
+        theme: &dto::Theme,
+        player: usize,
+        hole: usize,
+    ) -> Vec<VMixInterfacer<LeaderboardTop6>> {
         vec![
             VMixInterfacer::set_text(
                 "".to_string(),
                 LeaderboardTop6::LastScore { pos: player, hole },
             ),
             VMixInterfacer::set_color(
-                DEFAULT_FOREGROUND_COL_ALPHA,
+                &theme.hidden(),
                 LeaderboardTop6::LastScoreColour { hole, pos: player },
             ),
         ]
@@ -329,6 +338,13 @@ impl RustHandler {
             }
         }
 
+        let images = player_rounds
+            .iter()
+            .flatten()
+            .map(Player::image_request)
+            .collect_vec();
+        tokio::spawn(player_image::fetch_all(images));
+
         let mut container = PlayerContainer::new(player_rounds, round);
 
         for (round_number, round) in container.rounds_with_players.iter_mut().enumerate() {
@@ -402,6 +418,22 @@ impl RustHandler {
             player.fix_round_score(None);
         }
     }
+    /// Gives every player of the current round the stats of their earlier rounds
+    pub fn add_previous_stats_to_players(&mut self) {
+        let previous = self
+            .get_previous_rounds_players()
+            .into_iter()
+            .map(|player| (player.player_id.clone(), player.results.stats()))
+            .collect_vec();
+        for player in self.get_players_mut() {
+            player.previous_rounds_stats = previous
+                .iter()
+                .filter(|(id, _)| *id == player.player_id)
+                .map(|(_, stats)| *stats)
+                .sum();
+        }
+    }
+
     pub async fn get_event(
         event_ids: &[String],
         round_ids: &[Vec<String>],
@@ -502,8 +534,10 @@ impl RustHandler {
                 };
                 let mut return_map: HashMap<String, Holes> = HashMap::new();
                 for pool in round.pools {
-                    let holes =
-                        Holes::from_vec_hole(pool.layout_version.expect("Some layout").holes)?;
+                    let layout_version = pool.layout_version.expect("Some layout");
+                    let metadata = CourseMetadata::load(&layout_version.layout);
+                    let holes = Holes::from_vec_hole(layout_version.holes)?
+                        .with_metadata(metadata.into_holes());
                     for group in pool.groups {
                         return_map.insert(group.id.into_inner(), holes.clone());
                     }
@@ -2,7 +2,6 @@ use std::cmp::Ordering;
 use std::sync::Arc;
 
 use itertools::Itertools;
-use rayon::prelude::*;
 
 use crate::controller::fix_score;
 use crate::controller::queries::results_getter::HoleResult;
@@ -22,13 +21,16 @@ impl HoleStats {
             player_results,
         }
     }
-    pub fn average_score(&self, division: &Division) -> (isize, std::cmp::Ordering) {
-        let all_used_results = self
-            .player_results
+    fn division_results(&self, division: &Division) -> Vec<&HoleResult> {
+        self.player_results
             .iter()
             .filter(|(div, _)| div.id == division.id)
             .map(|(_, score)| score)
-            .collect_vec();
+            .collect_vec()
+    }
+
+    pub fn average_score(&self, division: &Division) -> (isize, std::cmp::Ordering) {
+        let all_used_results = self.division_results(division);
 
         let par = all_used_results.first().map(|res| res.par).unwrap_or({
             warn!("Par not found on Div: {}", division.short_name);
@@ -46,7 +48,153 @@ impl HoleStats {
             cmp,
         )
     }
+
+    pub fn distribution(&self, division: &Division) -> ScoreDistribution {
+        let mut distribution = ScoreDistribution::default();
+        for result in self.division_results(division) {
+            let category = ScoreCategory::new(result.score, result.par);
+            *distribution.count_mut(category) += 1;
+            if result.is_circle_hit {
+                distribution.circle_hits += 1;
+            }
+            // Tjing has no flag for parked, so a circle hit that was putted in
+            // for birdie or better is the closest we get
+            if result.is_circle_hit
+                && result.is_inside_putt
+                && category < ScoreCategory::Par
+                && category != ScoreCategory::Ace
+            {
+                distribution.parked += 1;
+            }
+            distribution.total += 1;
+        }
+        distribution
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScoreCategory {
+    Ace,
+    Eagle,
+    Birdie,
+    Par,
+    Bogey,
+    DoubleBogeyOrWorse,
+}
+
+impl ScoreCategory {
+    pub const ALL: [ScoreCategory; 6] = [
+        ScoreCategory::Ace,
+        ScoreCategory::Eagle,
+        ScoreCategory::Birdie,
+        ScoreCategory::Par,
+        ScoreCategory::Bogey,
+        ScoreCategory::DoubleBogeyOrWorse,
+    ];
+
+    pub fn new(score: usize, par: u8) -> Self {
+        if score == 1 {
+            return ScoreCategory::Ace;
+        }
+        match score as isize - par as isize {
+            ..=-2 => ScoreCategory::Eagle,
+            -1 => ScoreCategory::Birdie,
+            0 => ScoreCategory::Par,
+            1 => ScoreCategory::Bogey,
+            _ => ScoreCategory::DoubleBogeyOrWorse,
+        }
+    }
+
+    fn selection_name(&self) -> &'static str {
+        match self {
+            ScoreCategory::Ace => "ace",
+            ScoreCategory::Eagle => "eagle",
+            ScoreCategory::Birdie => "birdie",
+            ScoreCategory::Par => "par",
+            ScoreCategory::Bogey => "bogey",
+            ScoreCategory::DoubleBogeyOrWorse => "double",
+        }
+    }
+
+    fn text(&self) -> &'static str {
+        match self {
+            ScoreCategory::Ace => "ace",
+            ScoreCategory::Eagle => "eagle",
+            ScoreCategory::Birdie => "birdie",
+            ScoreCategory::Par => "par",
+            ScoreCategory::Bogey => "bogey",
+            ScoreCategory::DoubleBogeyOrWorse => "double bogey+",
+        }
+    }
+}
+
+/// How the results on a hole are spread out, for one division
+#[derive(Clone, Debug, Default)]
+pub struct ScoreDistribution {
+    pub aces: usize,
+    pub eagles: usize,
+    pub birdies: usize,
+    pub pars: usize,
+    pub bogeys: usize,
+    pub double_bogeys_or_worse: usize,
+    pub circle_hits: usize,
+    pub parked: usize,
+    pub total: usize,
+}
+
+impl ScoreDistribution {
+    pub fn count(&self, category: ScoreCategory) -> usize {
+        match category {
+            ScoreCategory::Ace => self.aces,
+            ScoreCategory::Eagle => self.eagles,
+            ScoreCategory::Birdie => self.birdies,
+            ScoreCategory::Par => self.pars,
+            ScoreCategory::Bogey => self.bogeys,
+            ScoreCategory::DoubleBogeyOrWorse => self.double_bogeys_or_worse,
+        }
+    }
+
+    fn count_mut(&mut self, category: ScoreCategory) -> &mut usize {
+        match category {
+            ScoreCategory::Ace => &mut self.aces,
+            ScoreCategory::Eagle => &mut self.eagles,
+            ScoreCategory::Birdie => &mut self.birdies,
+            ScoreCategory::Par => &mut self.pars,
+            ScoreCategory::Bogey => &mut self.bogeys,
+            ScoreCategory::DoubleBogeyOrWorse => &mut self.double_bogeys_or_worse,
+        }
+    }
+
+    /// Rounded percentage of all results on the hole
+    pub fn percentage(&self, count: usize) -> u8 {
+        if self.total == 0 {
+            return 0;
+        }
+        (count as f64 / self.total as f64 * 100.).round() as u8
+    }
+
+    pub fn vmix_info(&self) -> Vec<VMixHoleInfo> {
+        let mut info = ScoreCategory::ALL
+            .into_iter()
+            .flat_map(|category| {
+                let count = self.count(category);
+                [
+                    VMixHoleInfo::ScoreCount { category, count },
+                    VMixHoleInfo::ScoreRate {
+                        category,
+                        percentage: self.percentage(count),
+                    },
+                ]
+            })
+            .collect_vec();
+        info.push(VMixHoleInfo::CircleHitRate(
+            self.percentage(self.circle_hits),
+        ));
+        info.push(VMixHoleInfo::ParkedRate(self.percentage(self.parked)));
+        info
+    }
 }
+
 #[derive(Clone, Debug)]
 pub enum VMixHoleInfo {
     Hole(u8),
@@ -58,63 +206,129 @@ pub enum VMixHoleInfo {
         cmp: std::cmp::Ordering,
     },
     Difficulty {
+        /// Hole number
         hole: usize,
         difficulty: HoleDifficulty,
     },
     Elevation(i16),
+    ScoreCount {
+        category: ScoreCategory,
+        count: usize,
+    },
+    ScoreRate {
+        category: ScoreCategory,
+        percentage: u8,
+    },
+    CircleHitRate(u8),
+    ParkedRate(u8),
+    /// Average to par over every round of the event
+    EventAverage(f64),
+    /// 1 is the hardest hole of the event
+    EventDifficulty {
+        rank: usize,
+        holes: usize,
+        event: String,
+    },
+    /// Average to par over every stored event on the layout
+    AllTimeAverage(f64),
+    HoleName(String),
+    TeeDescription(String),
+    BasketDescription(String),
 }
+/// How hard the holes played, by their average score
 #[derive(Clone, Debug)]
 pub struct HoleDifficulty {
-    holes: Vec<u8>,
+    hole_numbers: Vec<u8>,
+    /// Average to par in tenths, like [`HoleStats::average_score`], in the order of `hole_numbers`
+    averages: Vec<isize>,
 }
 
 impl HoleDifficulty {
     pub fn new(holes: Vec<HoleStats>, division: &Division) -> Self {
+        let (hole_numbers, averages) = holes
+            .iter()
+            .sorted_by_key(|hole| hole.hole_number)
+            .map(|hole| (hole.hole_number, hole.average_score(division).0))
+            .unzip();
         Self {
-            holes: holes
-                .iter()
-                .sorted_by_key(|hole| hole.hole_number)
-                .map(|hole| {
-                    holes
-                        .iter()
-                        .filter(|other_hole| {
-                            hole.average_score(division).0 <= other_hole.average_score(division).0
-                        })
-                        .count() as u8
-                })
-                .collect(),
+            hole_numbers,
+            averages,
         }
     }
 
+    /// Hole number of the hole with the lowest average
+    pub fn easiest(&self) -> Option<u8> {
+        let index = self.averages.iter().position_min()?;
+        self.hole_numbers.get(index).copied()
+    }
+
+    /// Hole number of the hole with the highest average
+    pub fn hardest(&self) -> Option<u8> {
+        let index = self.averages.iter().position_max()?;
+        self.hole_numbers.get(index).copied()
+    }
+
+    /// 1 is the hardest, holes with the same average share a rank
+    fn hardest_rank(&self, average: isize) -> usize {
+        1 + self
+            .averages
+            .iter()
+            .filter(|other| **other > average)
+            .count()
+    }
+
+    /// 1 is the easiest
+    fn easiest_rank(&self, average: isize) -> usize {
+        1 + self
+            .averages
+            .iter()
+            .filter(|other| **other < average)
+            .count()
+    }
+
     fn hole_difficulty_text(&self, hole: usize) -> Option<String> {
-        let difficulty = self.holes.get(hole)?;
-        Some(match difficulty {
-            1 => "EASIEST".to_string(),
-            2 => "2nd easiest".to_string(),
-            3 => "3rd easiest".to_string(),
-            4..=9 => format!("{difficulty}th easiest"),
-            10..=15 => format!("{}th hardest", 18 - difficulty),
-            16 => "3rd hardest".to_string(),
-            17 => "2nd hardest".to_string(),
-            18 => "HARDEST".to_string(),
-            _ => None?,
-        })
+        let index = self
+            .hole_numbers
+            .iter()
+            .position(|number| *number as usize == hole)?;
+        let average = self.averages[index];
+        Some(
+            match (self.hardest_rank(average), self.easiest_rank(average)) {
+                (1, _) => "HARDEST".to_string(),
+                (_, 1) => "EASIEST".to_string(),
+                (hardest, easiest) if hardest <= easiest => {
+                    format!("{} hardest", ordinal(hardest))
+                }
+                (_, easiest) => format!("{} easiest", ordinal(easiest)),
+            },
+        )
     }
 }
 
 impl VMixSelectionTrait for VMixHoleInfo {
+    const GRAPHIC_NAME: &'static str = "VMixHoleInfo";
+
     fn get_selection_name(&self) -> String {
         use VMixHoleInfo::*;
         match self {
-            Hole(_) => "hole",
-            HolePar(_) => "parnr",
-            HoleMeters(_) => "meternr",
-            HoleFeet(_) => "feetnr",
-            AverageResult { .. } => "avgresult",
-            Difficulty { .. } => "difficulty",
-            Elevation(_) => "elevation",
+            Hole(_) => "hole".to_string(),
+            HolePar(_) => "parnr".to_string(),
+            HoleMeters(_) => "meternr".to_string(),
+            HoleFeet(_) => "feetnr".to_string(),
+            AverageResult { .. } => "avgresult".to_string(),
+            Difficulty { .. } => "difficulty".to_string(),
+            Elevation(_) => "elevation".to_string(),
+            ScoreCount { category, .. } => format!("{}count", category.selection_name()),
+            ScoreRate { category, .. } => format!("{}rate", category.selection_name()),
+            CircleHitRate(_) => "c1rate".to_string(),
+            ParkedRate(_) => "parkedrate".to_string(),
+            EventAverage(_) => "eventavg".to_string(),
+            EventDifficulty { .. } => "eventdifficulty".to_string(),
+            AllTimeAverage(_) => "alltimeavg".to_string(),
+            HoleName(_) => "holename".to_string(),
+            TeeDescription(_) => "teedesc".to_string(),
+            BasketDescription(_) => "basketdesc".to_string(),
         }
-        .to_string()
     }
 
     fn data_extension(&self) -> &'static str {
@@ -142,8 +356,29 @@ impl VMixSelectionTrait for VMixHoleInfo {
                     Ordering::Less => format!("%2B{rest}.{decimal}"),
                 }) + " avg"
             }
-            Difficulty { difficulty, hole } => difficulty.hole_difficulty_text(*hole).unwrap(),
+            Difficulty { difficulty, hole } => difficulty.hole_difficulty_text(*hole)?,
             Elevation(elevation) => fix_score(*elevation as isize),
+            ScoreCount { count, .. } => count.to_string(),
+            ScoreRate {
+                category,
+                percentage,
+            } => format!("{percentage}% {} rate", category.text()),
+            CircleHitRate(percentage) => format!("{percentage}% C1 in regulation"),
+            ParkedRate(percentage) => format!("{percentage}% parked"),
+            EventAverage(average) => format!("Played {} all week", to_par_text(*average)),
+            EventDifficulty { rank, holes, event } => {
+                let easiest_rank = holes + 1 - rank;
+                match (rank, easiest_rank) {
+                    (1, _) => format!("Hardest hole of the {event}"),
+                    (_, 1) => format!("Easiest hole of the {event}"),
+                    (rank, easiest) if *rank <= easiest => {
+                        format!("{} hardest hole of the {event}", ordinal(*rank))
+                    }
+                    (_, easiest) => format!("{} easiest hole of the {event}", ordinal(easiest)),
+                }
+            }
+            AllTimeAverage(average) => format!("Played {} all time", to_par_text(*average)),
+            HoleName(text) | TeeDescription(text) | BasketDescription(text) => text.clone(),
         })
     }
     fn input_id(&self) -> &'static str {
@@ -151,8 +386,31 @@ impl VMixSelectionTrait for VMixHoleInfo {
     }
 }
 
+fn to_par_text(average: f64) -> String {
+    if average > 0. {
+        format!("{average:.1} over par")
+    } else if average < 0. {
+        format!("{:.1} under par", -average)
+    } else {
+        "even par".to_string()
+    }
+}
+
+fn ordinal(number: usize) -> String {
+    let suffix = match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{number}{suffix}")
+}
+
 pub struct FeaturedHole(pub VMixHoleInfo);
 impl VMixSelectionTrait for FeaturedHole {
+    const GRAPHIC_NAME: &'static str = "FeaturedHole";
+
     fn get_selection_name(&self) -> String {
         self.0.get_selection_name()
     }
@@ -175,6 +433,8 @@ pub enum DroneHoleInfo {
     HoleMap,
 }
 impl VMixSelectionTrait for DroneHoleInfo {
+    const GRAPHIC_NAME: &'static str = "DroneHoleInfo";
+
     fn get_selection_name(&self) -> String {
         match self {
             DroneHoleInfo::Standard(s) => s.get_selection_name(),
@@ -200,3 +460,62 @@ impl VMixSelectionTrait for DroneHoleInfo {
         "d135d6d1-11ee-4169-9700-4c743d729218"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(hole_number: usize, score: usize) -> HoleResult {
+        HoleResult {
+            score,
+            hole_number,
+            par: 3,
+            is_circle_hit: false,
+            is_inside_putt: false,
+            is_out_of_bounds: false,
+            is_outside_putt: false,
+            is_verified: true,
+        }
+    }
+
+    /// Holes 1, 2 and 3 averaging 3.0, 4.0 and 2.5
+    fn difficulty() -> HoleDifficulty {
+        let division = Arc::new(Division::default());
+        let hole = |number: u8, scores: [usize; 2]| {
+            HoleStats::new(
+                number,
+                scores
+                    .into_iter()
+                    .map(|score| (division.clone(), result(number as usize, score)))
+                    .collect(),
+            )
+        };
+        let holes = vec![hole(2, [4, 4]), hole(3, [2, 3]), hole(1, [3, 3])];
+        HoleDifficulty::new(holes, &division)
+    }
+
+    #[test]
+    fn hardest_hole_has_highest_average() {
+        let difficulty = difficulty();
+        assert_eq!(difficulty.hardest(), Some(2));
+        assert_eq!(difficulty.easiest(), Some(3));
+    }
+
+    #[test]
+    fn text_follows_the_same_ranking() {
+        let difficulty = difficulty();
+        assert_eq!(
+            difficulty.hole_difficulty_text(2).as_deref(),
+            Some("HARDEST")
+        );
+        assert_eq!(
+            difficulty.hole_difficulty_text(3).as_deref(),
+            Some("EASIEST")
+        );
+        assert_eq!(
+            difficulty.hole_difficulty_text(1).as_deref(),
+            Some("2nd hardest")
+        );
+        assert_eq!(difficulty.hole_difficulty_text(4), None);
+    }
+}
@@ -0,0 +1,311 @@
+//! Results per layout and hole that outlive a single round.
+//!
+//! Every verified result is kept in [`HOLE_HISTORY_FILE`], so the averages cover all rounds of the
+//! event and earlier events played on the same layout, also after a restart.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use itertools::Itertools;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::controller::hole::VMixHoleInfo;
+use crate::controller::queries::Division;
+use crate::controller::Player;
+use crate::dto;
+use crate::json_file::{load_json_file, save_json_file};
+
+pub const HOLE_HISTORY_FILE: &str = "hole_history.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HoleHistory {
+    #[serde(serialize_with = "as_list", deserialize_with = "from_list")]
+    events: HashMap<String, EventHistory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EventHistory {
+    event_id: String,
+    /// Shown on the hole graphic, like "2025 championship". Falls back to "event"
+    name: Option<String>,
+    #[serde(serialize_with = "as_list", deserialize_with = "from_list")]
+    results: HashMap<ResultKey, HoleRecord>,
+}
+
+/// One result per player, hole and round on a layout
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ResultKey {
+    layout: String,
+    hole: u8,
+    round: usize,
+    player_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct HoleRecord {
+    layout: String,
+    hole: u8,
+    round: usize,
+    player_id: String,
+    division: String,
+    score: usize,
+    par: u8,
+}
+
+/// Entries that know their own key, stored in the file as a plain list
+trait Keyed {
+    type Key: Eq + Hash;
+    fn key(&self) -> Self::Key;
+}
+
+impl Keyed for EventHistory {
+    type Key = String;
+    fn key(&self) -> String {
+        self.event_id.clone()
+    }
+}
+
+impl Keyed for HoleRecord {
+    type Key = ResultKey;
+    fn key(&self) -> ResultKey {
+        ResultKey {
+            layout: self.layout.clone(),
+            hole: self.hole,
+            round: self.round,
+            player_id: self.player_id.clone(),
+        }
+    }
+}
+
+fn as_list<K, V: Serialize, S: Serializer>(
+    map: &HashMap<K, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(map.values())
+}
+
+fn from_list<'de, D: Deserializer<'de>, V: Keyed + Deserialize<'de>>(
+    deserializer: D,
+) -> Result<HashMap<V::Key, V>, D::Error> {
+    let list = Vec::<V>::deserialize(deserializer)?;
+    Ok(list.into_iter().map(|value| (value.key(), value)).collect())
+}
+
+impl HoleRecord {
+    fn to_par(&self) -> isize {
+        self.score as isize - self.par as isize
+    }
+}
+
+impl EventHistory {
+    /// True if the result was not stored like this already
+    fn insert(&mut self, record: HoleRecord) -> bool {
+        match self.results.get(&record.key()) {
+            Some(existing) if *existing == record => false,
+            _ => {
+                self.results.insert(record.key(), record);
+                true
+            }
+        }
+    }
+}
+
+/// Which results an average or ranking is made from
+#[derive(Debug, Clone, Copy)]
+pub enum HistoryScope<'a> {
+    Event(&'a str),
+    AllEvents,
+}
+
+impl HoleHistory {
+    pub fn load() -> Self {
+        load_json_file(HOLE_HISTORY_FILE)
+    }
+
+    fn save(&self) {
+        save_json_file(HOLE_HISTORY_FILE, self);
+    }
+
+    fn event_mut(&mut self, event_id: &str) -> &mut EventHistory {
+        self.events
+            .entry(event_id.to_string())
+            .or_insert_with(|| EventHistory {
+                event_id: event_id.to_string(),
+                name: None,
+                results: HashMap::new(),
+            })
+    }
+
+    /// Adds the verified results of the players, and saves if anything changed.
+    ///
+    /// `event_ids` is indexed by the event number of the players.
+    pub fn record(&mut self, event_ids: &[String], players: &[&Player]) {
+        if self.add_results(event_ids, players) {
+            self.save();
+        }
+    }
+
+    /// True if a result was new or corrected
+    fn add_results(&mut self, event_ids: &[String], players: &[&Player]) -> bool {
+        let mut changed = false;
+        for player in players {
+            let Some(event_id) = event_ids.get(player.event_number) else {
+                continue;
+            };
+            let layout = player.layout.key();
+            let event = self.event_mut(event_id);
+            for result in player.results.verified_results() {
+                let record = HoleRecord {
+                    layout: layout.clone(),
+                    hole: result.hole_number as u8,
+                    round: player.round_ind,
+                    player_id: player.player_id.clone(),
+                    division: player.division.short_name.clone(),
+                    score: result.score,
+                    par: result.par,
+                };
+                changed |= event.insert(record);
+            }
+        }
+        changed
+    }
+
+    pub fn set_event_name(&mut self, event_id: &str, name: String) {
+        self.event_mut(event_id).name = Some(name);
+        self.save();
+    }
+
+    fn event_name(&self, event_id: &str) -> String {
+        self.events
+            .get(event_id)
+            .and_then(|event| event.name.clone())
+            .unwrap_or("event".to_string())
+    }
+
+    fn results<'a>(
+        &'a self,
+        layout: &'a str,
+        division: &'a Division,
+        scope: HistoryScope<'a>,
+    ) -> impl Iterator<Item = &'a HoleRecord> {
+        self.events
+            .values()
+            .filter(move |event| match scope {
+                HistoryScope::Event(event_id) => event.event_id == event_id,
+                HistoryScope::AllEvents => true,
+            })
+            .flat_map(|event| event.results.values())
+            .filter(move |result| result.layout == layout && result.division == division.short_name)
+    }
+
+    /// Average score to par of every hole on the layout that has results
+    fn averages(
+        &self,
+        layout: &str,
+        division: &Division,
+        scope: HistoryScope,
+    ) -> HashMap<u8, (f64, usize)> {
+        self.results(layout, division, scope)
+            .into_group_map_by(|result| result.hole)
+            .into_iter()
+            .map(|(hole, results)| {
+                let sum = results.iter().map(|result| result.to_par()).sum::<isize>();
+                (hole, (sum as f64 / results.len() as f64, results.len()))
+            })
+            .collect()
+    }
+
+    /// Average to par and amount of results on the hole
+    pub fn average(
+        &self,
+        layout: &str,
+        hole: u8,
+        division: &Division,
+        scope: HistoryScope,
+    ) -> Option<(f64, usize)> {
+        self.averages(layout, division, scope).get(&hole).copied()
+    }
+
+    /// 1 is the hardest hole, together with the amount of holes ranked
+    pub fn hardest_rank(
+        &self,
+        layout: &str,
+        hole: u8,
+        division: &Division,
+        scope: HistoryScope,
+    ) -> Option<(usize, usize)> {
+        let averages = self.averages(layout, division, scope);
+        let (average, _) = averages.get(&hole)?;
+        let rank = averages
+            .values()
+            .filter(|(other, _)| other > average)
+            .count()
+            + 1;
+        Some((rank, averages.len()))
+    }
+
+    pub fn to_dto(
+        &self,
+        layout: &str,
+        hole: u8,
+        division: &Division,
+        event_id: &str,
+    ) -> dto::HoleHistory {
+        let event = HistoryScope::Event(event_id);
+        let event_average = self.average(layout, hole, division, event);
+        let all_time_average = self.average(layout, hole, division, HistoryScope::AllEvents);
+        dto::HoleHistory {
+            layout: layout.to_string(),
+            hole,
+            division: division.short_name.clone(),
+            event_name: self.event_name(event_id),
+            event_average: event_average.map(|(average, _)| Self::round(average)),
+            event_results: event_average.map(|(_, count)| count).unwrap_or_default(),
+            event_hardest_rank: self
+                .hardest_rank(layout, hole, division, event)
+                .map(|(rank, _)| rank),
+            all_time_average: all_time_average.map(|(average, _)| Self::round(average)),
+            all_time_results: all_time_average.map(|(_, count)| count).unwrap_or_default(),
+            events: self
+                .events
+                .values()
+                .filter(|event| {
+                    event.results.values().any(|result| {
+                        result.layout == layout
+                            && result.hole == hole
+                            && result.division == division.short_name
+                    })
+                })
+                .count(),
+        }
+    }
+
+    fn round(average: f64) -> f64 {
+        (average * 10.).round() / 10.
+    }
+
+    pub fn vmix_info(
+        &self,
+        layout: &str,
+        hole: u8,
+        division: &Division,
+        event_id: &str,
+    ) -> Vec<VMixHoleInfo> {
+        let mut info = vec![];
+        let event = HistoryScope::Event(event_id);
+        if let Some((average, _)) = self.average(layout, hole, division, event) {
+            info.push(VMixHoleInfo::EventAverage(Self::round(average)));
+        }
+        if let Some((rank, holes)) = self.hardest_rank(layout, hole, division, event) {
+            info.push(VMixHoleInfo::EventDifficulty {
+                rank,
+                holes,
+                event: self.event_name(event_id),
+            });
+        }
+        if let Some((average, _)) = self.average(layout, hole, division, HistoryScope::AllEvents) {
+            info.push(VMixHoleInfo::AllTimeAverage(Self::round(average)));
+        }
+        info
+    }
+}
@@ -2,6 +2,11 @@ pub use coordinator::Player;
 pub use get_data::fix_score;
 
 pub mod coordinator;
+pub(crate) mod course_metadata;
 pub(crate) mod get_data;
 pub(crate) mod hole;
+pub(crate) mod hole_history;
+pub(crate) mod player_image;
 pub(crate) mod queries;
+pub(crate) mod stats;
+pub mod virtual_division;
@@ -0,0 +1,219 @@
+//! Player images for the titles, prepared in the background and kept between runs.
+//!
+//! Downloads are kept in `cache/<width>x<height>` in the image cache folder, by a hash of their URL,
+//! as PNGs cropped to the size of the title image. An image in `overrides` named after the player id
+//! or PDGA number, like `12345.jpg`, replaces the downloaded one. Players without either get
+//! `silhouette.png`. The folder and size are set in [`crate::assets`].
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use image::imageops::FilterType;
+use image::{ImageFormat, Rgba, RgbaImage};
+use itertools::Itertools;
+use log::warn;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::assets;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// Images downloaded or converted at the same time
+const MAX_CONCURRENT: usize = 4;
+const OVERRIDE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "gif"];
+
+/// Where the image of every prepared player is, so titles never wait on the disk
+static LOCATIONS: OnceLock<RwLock<HashMap<String, PathBuf>>> = OnceLock::new();
+/// Numbers the temporary files, players sharing a photo URL convert to the same destination
+static CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn locations() -> &'static RwLock<HashMap<String, PathBuf>> {
+    LOCATIONS.get_or_init(Default::default)
+}
+
+fn images_dir() -> PathBuf {
+    assets::get().image_cache()
+}
+
+/// Images of another size are kept apart, so changing the size makes new ones
+fn cache_dir() -> PathBuf {
+    let (width, height) = assets::get().player_image_size();
+    images_dir().join("cache").join(format!("{width}x{height}"))
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageRequest {
+    pub player_id: String,
+    pub pdga_number: Option<u32>,
+    pub url: Option<String>,
+}
+
+/// FNV-1a, which unlike the std hasher stays the same between builds
+fn url_hash(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn cached_path(url: &str) -> PathBuf {
+    cache_dir().join(format!("{}.png", url_hash(url)))
+}
+
+fn override_path(player_id: &str) -> PathBuf {
+    cache_dir().join(format!("override_{player_id}.png"))
+}
+
+fn silhouette_path() -> PathBuf {
+    cache_dir().join("silhouette.png")
+}
+
+/// The override or the downloaded image, or the silhouette until it is prepared
+pub fn location(player_id: &str) -> PathBuf {
+    locations()
+        .read()
+        .unwrap()
+        .get(player_id)
+        .cloned()
+        .unwrap_or_else(silhouette_path)
+}
+
+/// Prepares the image of every player that does not have one yet
+pub async fn fetch_all(requests: Vec<ImageRequest>) {
+    if let Err(e) = tokio::fs::create_dir_all(cache_dir()).await {
+        warn!("Unable to create the image cache: {e}");
+        return;
+    }
+    match tokio::task::spawn_blocking(write_silhouette).await {
+        Ok(Ok(())) => (),
+        Ok(Err(e)) => warn!("Unable to make the silhouette image: {e}"),
+        Err(e) => warn!("Unable to make the silhouette image: {e}"),
+    }
+
+    let client = reqwest::Client::new();
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let mut tasks = JoinSet::new();
+    for request in requests
+        .into_iter()
+        .unique_by(|request| request.player_id.clone())
+    {
+        let client = client.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            match prepare(&client, &request).await {
+                Ok(Some(location)) => {
+                    locations()
+                        .write()
+                        .unwrap()
+                        .insert(request.player_id, location);
+                }
+                Ok(None) => (),
+                Err(e) => warn!("No image for player {}: {e}", request.player_id),
+            }
+        });
+    }
+    while tasks.join_next().await.is_some() {}
+}
+
+/// Where the image of the player is once it is ready, none for the silhouette
+async fn prepare(
+    client: &reqwest::Client,
+    request: &ImageRequest,
+) -> Result<Option<PathBuf>, BoxError> {
+    let destination = override_path(&request.player_id);
+    if let Some(source) = find_override(request) {
+        if is_outdated(&destination, &source) {
+            let bytes = tokio::fs::read(&source).await?;
+            convert_in_background(bytes, destination.clone()).await?;
+        }
+        return Ok(Some(destination));
+    } else if destination.exists() {
+        // The override was removed
+        tokio::fs::remove_file(&destination).await?;
+    }
+
+    let Some(url) = &request.url else {
+        return Ok(None);
+    };
+    let destination = cached_path(url);
+    if !destination.exists() {
+        let response = client.get(url).send().await?.error_for_status()?;
+        let bytes = response.bytes().await?.to_vec();
+        convert_in_background(bytes, destination.clone()).await?;
+    }
+    Ok(Some(destination))
+}
+
+fn find_override(request: &ImageRequest) -> Option<PathBuf> {
+    let overrides = images_dir().join("overrides");
+    let names = [
+        Some(request.player_id.clone()),
+        request.pdga_number.map(|number| number.to_string()),
+    ];
+    names
+        .into_iter()
+        .flatten()
+        .cartesian_product(OVERRIDE_EXTENSIONS)
+        .map(|(name, extension)| overrides.join(format!("{name}.{extension}")))
+        .find(|path| path.exists())
+}
+
+fn is_outdated(destination: &Path, source: &Path) -> bool {
+    let modified = |path: &Path| {
+        path.metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    match (modified(destination), modified(source)) {
+        (Some(destination), Some(source)) => destination < source,
+        _ => true,
+    }
+}
+
+async fn convert_in_background(bytes: Vec<u8>, destination: PathBuf) -> Result<(), BoxError> {
+    tokio::task::spawn_blocking(move || convert(&bytes, &destination)).await?
+}
+
+/// Crops to the title size, and writes to a temporary file next to the destination first so a half
+/// written image is never shown
+fn convert(bytes: &[u8], destination: &Path) -> Result<(), BoxError> {
+    let format = image::guess_format(bytes)?;
+    let image = image::load_from_memory_with_format(bytes, format)?;
+    let conversion = CONVERSIONS.fetch_add(1, Ordering::Relaxed);
+    let temporary = destination.with_extension(format!("{conversion}.png.tmp"));
+    let (width, height) = assets::get().player_image_size();
+    image
+        .resize_to_fill(width, height, FilterType::Lanczos3)
+        .save_with_format(&temporary, ImageFormat::Png)?;
+    std::fs::rename(temporary, destination)?;
+    Ok(())
+}
+
+/// A head and shoulders, in the colours of the titles
+fn write_silhouette() -> Result<(), BoxError> {
+    let path = silhouette_path();
+    if path.exists() {
+        return Ok(());
+    }
+    let background = Rgba([0x3F, 0x33, 0x4D, 0xFF]);
+    let figure = Rgba([0x8E, 0x86, 0x99, 0xFF]);
+    let (width, height) = assets::get().player_image_size();
+    let image = RgbaImage::from_fn(width, height, |x, y| {
+        let x = x as f32 / width as f32 - 0.5;
+        let y = y as f32 / height as f32;
+        let head = x.powi(2) + (y - 0.38).powi(2) < 0.18f32.powi(2);
+        let shoulders = (x / 0.36).powi(2) + ((y - 1.0) / 0.38).powi(2) < 1.0;
+        if head || shoulders {
+            figure
+        } else {
+            background
+        }
+    });
+    image.save_with_format(path, ImageFormat::Png)?;
+    Ok(())
+}
@@ -299,11 +299,13 @@ pub mod layout {
     use super::schema;
 
     pub mod hole {
+        use std::collections::HashMap;
         use std::sync::Arc;
 
         use itertools::Itertools;
 
         use crate::api::Error;
+        use crate::dto::HoleMetadata;
 
         #[derive(Debug, Clone, Default)]
         pub struct Holes {
@@ -326,6 +328,22 @@ pub mod layout {
                 let holes = holes.into_iter().map(Arc::new).collect();
                 Ok(Self { holes })
             }
+
+            /// Fills in what is known about the holes from the course metadata
+            pub fn with_metadata(self, mut metadata: HashMap<u8, HoleMetadata>) -> Self {
+                let holes = self
+                    .holes
+                    .into_iter()
+                    .map(|hole| match metadata.remove(&hole.hole) {
+                        Some(metadata) => Arc::new(Hole {
+                            metadata,
+                            ..(*hole).clone()
+                        }),
+                        None => hole,
+                    })
+                    .collect();
+                Self { holes }
+            }
         }
 
         #[derive(Debug, Clone, Default)]
@@ -333,6 +351,7 @@ pub mod layout {
             pub length: u16,
             pub par: u8,
             pub hole: u8,
+            pub metadata: HoleMetadata,
         }
 
         impl TryFrom<super::Hole> for Hole {
@@ -356,6 +375,10 @@ pub mod layout {
                     length,
                     par,
                     hole: hole_number,
+                    metadata: HoleMetadata {
+                        hole: hole_number,
+                        ..Default::default()
+                    },
                 })
             }
         }
@@ -412,6 +435,16 @@ pub mod layout {
         pub course: Option<Course>,
     }
 
+    impl Layout {
+        /// Course and layout name, the same layout keeps the same key between events
+        pub fn key(&self) -> String {
+            match &self.course {
+                Some(course) => format!("{} - {}", course.name, self.name),
+                None => self.name.clone(),
+            }
+        }
+    }
+
     #[derive(cynic::QueryFragment, Debug, Clone)]
     pub struct Course {
         pub name: String,
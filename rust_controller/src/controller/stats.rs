@@ -0,0 +1,273 @@
+//! Putting and driving statistics from the flags Tjing sets on every hole result.
+//!
+//! Tjing only tells us which putts were made, not which were attempted, so the putting
+//! percentages use the holes where the player most likely putted from that distance:
+//! C1X putting counts the holes where C1 was reached in regulation. There is no C2 putting
+//! percentage, only how often a C2 putt was made on the holes where C1 was not reached in
+//! regulation, which also counts holes where the approach ended inside C1.
+//! C2 regulation is C1 regulation, or a made C2 putt for birdie or better. A C2 putt made for
+//! par is a scramble, not regulation.
+
+use std::iter::Sum;
+use std::ops::Add;
+
+use crate::controller::queries::results_getter::HoleResult;
+use crate::dto;
+use crate::vmix::functions::{VMixInterfacer, VMixPlayerInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerStat {
+    C1xPutting,
+    /// Made C2 putts of the holes without C1 in regulation, not of the C2 putts attempted
+    C2MadeWithoutC1Regulation,
+    C1Regulation,
+    C2Regulation,
+    Scramble,
+    OutOfBounds,
+    Birdie,
+    BogeyAvoidance,
+}
+
+impl PlayerStat {
+    pub const ALL: [PlayerStat; 8] = [
+        PlayerStat::C1xPutting,
+        PlayerStat::C2MadeWithoutC1Regulation,
+        PlayerStat::C1Regulation,
+        PlayerStat::C2Regulation,
+        PlayerStat::Scramble,
+        PlayerStat::OutOfBounds,
+        PlayerStat::Birdie,
+        PlayerStat::BogeyAvoidance,
+    ];
+
+    pub fn selection_name(&self) -> &'static str {
+        match self {
+            PlayerStat::C1xPutting => "c1xputt",
+            PlayerStat::C2MadeWithoutC1Regulation => "c2made",
+            PlayerStat::C1Regulation => "c1regstat",
+            PlayerStat::C2Regulation => "c2reg",
+            PlayerStat::Scramble => "scramble",
+            PlayerStat::OutOfBounds => "obrate",
+            PlayerStat::Birdie => "birdierate",
+            PlayerStat::BogeyAvoidance => "bogeyavoid",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatScope {
+    Round,
+    Event,
+    PreviousRounds,
+}
+
+impl StatScope {
+    pub const ALL: [StatScope; 3] = [
+        StatScope::Round,
+        StatScope::Event,
+        StatScope::PreviousRounds,
+    ];
+
+    pub fn selection_suffix(&self) -> &'static str {
+        match self {
+            StatScope::Round => "",
+            StatScope::Event => "evt",
+            StatScope::PreviousRounds => "prev",
+        }
+    }
+}
+
+/// Counts behind the statistics, kept as counts so that rounds can be added together
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatCounts {
+    pub holes: usize,
+    pub c1_regulation: usize,
+    pub c2_regulation: usize,
+    pub c1x_made: usize,
+    pub missed_c1_regulation: usize,
+    pub c2_made: usize,
+    pub inside_putts: usize,
+    pub scramble_chances: usize,
+    pub scrambles: usize,
+    pub out_of_bounds: usize,
+    pub birdies: usize,
+    pub par_or_better: usize,
+}
+
+impl StatCounts {
+    /// Only verified results are counted
+    pub fn new<'a>(results: impl IntoIterator<Item = &'a HoleResult>) -> Self {
+        results
+            .into_iter()
+            .filter(|result| result.is_verified)
+            .map(Self::from_result)
+            .sum()
+    }
+
+    fn from_result(result: &HoleResult) -> Self {
+        let to_par = result.score as isize - result.par as isize;
+        let c2_regulation = result.is_circle_hit || (result.is_outside_putt && to_par < 0);
+        let scramble_chance = !c2_regulation;
+        let count = |condition: bool| condition as usize;
+        Self {
+            holes: 1,
+            c1_regulation: count(result.is_circle_hit),
+            c2_regulation: count(c2_regulation),
+            c1x_made: count(result.is_circle_hit && result.is_inside_putt),
+            missed_c1_regulation: count(!result.is_circle_hit),
+            c2_made: count(!result.is_circle_hit && result.is_outside_putt),
+            inside_putts: count(result.is_inside_putt),
+            scramble_chances: count(scramble_chance),
+            scrambles: count(scramble_chance && to_par <= 0),
+            out_of_bounds: count(result.is_out_of_bounds),
+            birdies: count(to_par < 0),
+            par_or_better: count(to_par <= 0),
+        }
+    }
+
+    fn rate(made: usize, attempts: usize) -> Option<u8> {
+        if attempts == 0 {
+            return None;
+        }
+        Some((made as f64 / attempts as f64 * 100.).round() as u8)
+    }
+
+    /// Rounded percentage, none if there was nothing to count yet
+    pub fn percentage(&self, stat: PlayerStat) -> Option<u8> {
+        match stat {
+            PlayerStat::C1xPutting => Self::rate(self.c1x_made, self.c1_regulation),
+            PlayerStat::C2MadeWithoutC1Regulation => {
+                Self::rate(self.c2_made, self.missed_c1_regulation)
+            }
+            PlayerStat::C1Regulation => Self::rate(self.c1_regulation, self.holes),
+            PlayerStat::C2Regulation => Self::rate(self.c2_regulation, self.holes),
+            PlayerStat::Scramble => Self::rate(self.scrambles, self.scramble_chances),
+            PlayerStat::OutOfBounds => Self::rate(self.out_of_bounds, self.holes),
+            PlayerStat::Birdie => Self::rate(self.birdies, self.holes),
+            PlayerStat::BogeyAvoidance => Self::rate(self.par_or_better, self.holes),
+        }
+    }
+
+    pub fn inside_putt_percentage(&self) -> Option<u8> {
+        Self::rate(self.inside_putts, self.holes)
+    }
+
+    pub fn to_vmix_instructions(
+        self,
+        index: usize,
+        scope: StatScope,
+    ) -> Vec<VMixInterfacer<VMixPlayerInfo>> {
+        PlayerStat::ALL
+            .into_iter()
+            .map(|stat| {
+                VMixInterfacer::set_text(
+                    self.percentage(stat)
+                        .map(|percentage| format!("{percentage}%"))
+                        .unwrap_or("-".to_string()),
+                    VMixPlayerInfo::Stat { index, stat, scope },
+                )
+            })
+            .collect()
+    }
+
+    pub fn to_dto(self) -> dto::PlayerStats {
+        dto::PlayerStats {
+            holes: self.holes,
+            c1x_putting: self.percentage(PlayerStat::C1xPutting),
+            c2_made_without_c1_regulation: self.percentage(PlayerStat::C2MadeWithoutC1Regulation),
+            c1_regulation: self.percentage(PlayerStat::C1Regulation),
+            c2_regulation: self.percentage(PlayerStat::C2Regulation),
+            scramble: self.percentage(PlayerStat::Scramble),
+            out_of_bounds: self.percentage(PlayerStat::OutOfBounds),
+            birdie: self.percentage(PlayerStat::Birdie),
+            bogey_avoidance: self.percentage(PlayerStat::BogeyAvoidance),
+        }
+    }
+}
+
+impl Add for StatCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            holes: self.holes + other.holes,
+            c1_regulation: self.c1_regulation + other.c1_regulation,
+            c2_regulation: self.c2_regulation + other.c2_regulation,
+            c1x_made: self.c1x_made + other.c1x_made,
+            missed_c1_regulation: self.missed_c1_regulation + other.missed_c1_regulation,
+            c2_made: self.c2_made + other.c2_made,
+            inside_putts: self.inside_putts + other.inside_putts,
+            scramble_chances: self.scramble_chances + other.scramble_chances,
+            scrambles: self.scrambles + other.scrambles,
+            out_of_bounds: self.out_of_bounds + other.out_of_bounds,
+            birdies: self.birdies + other.birdies,
+            par_or_better: self.par_or_better + other.par_or_better,
+        }
+    }
+}
+
+impl Sum for StatCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(score: usize, circle_hit: bool, inside: bool, outside: bool) -> HoleResult {
+        HoleResult {
+            score,
+            hole_number: 1,
+            par: 3,
+            is_circle_hit: circle_hit,
+            is_inside_putt: inside,
+            is_out_of_bounds: false,
+            is_outside_putt: outside,
+            is_verified: true,
+        }
+    }
+
+    #[test]
+    fn putting_is_counted_from_regulation() {
+        let results = [
+            // C1 in regulation, made for birdie
+            result(2, true, true, false),
+            // C1 in regulation, missed
+            result(3, true, false, false),
+            // C2 putt made for par, a scramble
+            result(3, false, false, true),
+            // Approach inside C1, counted as a hole without a C2 make
+            result(3, false, true, false),
+        ];
+        let counts = StatCounts::new(&results);
+        assert_eq!(counts.percentage(PlayerStat::C1xPutting), Some(50));
+        assert_eq!(
+            counts.percentage(PlayerStat::C2MadeWithoutC1Regulation),
+            Some(50)
+        );
+        assert_eq!(counts.percentage(PlayerStat::C1Regulation), Some(50));
+        assert_eq!(counts.percentage(PlayerStat::C2Regulation), Some(50));
+        assert_eq!(counts.percentage(PlayerStat::Scramble), Some(100));
+        assert_eq!(counts.percentage(PlayerStat::Birdie), Some(25));
+    }
+
+    #[test]
+    fn unverified_results_are_not_counted() {
+        let mut unverified = result(2, true, true, false);
+        unverified.is_verified = false;
+        let counts = StatCounts::new([&unverified]);
+        assert_eq!(counts.holes, 0);
+        assert_eq!(counts.percentage(PlayerStat::C1xPutting), None);
+    }
+
+    #[test]
+    fn rounds_add_up() {
+        let first = StatCounts::new(&[result(2, true, true, false)]);
+        let second = StatCounts::new(&[result(4, false, false, false)]);
+        let event = first + second;
+        assert_eq!(event.holes, 2);
+        assert_eq!(event.percentage(PlayerStat::BogeyAvoidance), Some(50));
+    }
+}
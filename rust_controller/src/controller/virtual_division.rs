@@ -0,0 +1,181 @@
+//! Divisions that do not exist in Tjing, made up of players from other divisions.
+//!
+//! Used for overall leaderboards across several divisions, or groups like juniors and local club
+//! players. Leaderboards look them up here by division id, so they work anywhere a normal division does.
+
+use std::sync::{Arc, RwLock};
+
+use crate::api::Error;
+use crate::controller::queries::Division;
+use crate::controller::Player;
+use crate::dto;
+use crate::json_file::{load_json_file, save_json_file};
+
+pub const VIRTUAL_DIVISIONS_FILE: &str = "virtual_divisions.json";
+
+/// The virtual divisions of a coordinator. Clones share the divisions, so the leaderboards of the
+/// coordinator see the ones added after they were made
+#[derive(Debug, Clone, Default)]
+pub struct VirtualDivisions(Arc<RwLock<Vec<VirtualDivision>>>);
+
+#[derive(Debug, Clone)]
+struct VirtualDivision {
+    division: Arc<Division>,
+    definition: dto::VirtualDivision,
+}
+
+impl VirtualDivision {
+    /// Short names are unique, so they make the id
+    fn new(definition: dto::VirtualDivision) -> Self {
+        Self {
+            division: Arc::new(Division {
+                name: definition.name.clone(),
+                short_name: definition.short_name.clone(),
+                id: cynic::Id::new(format!("virtual:{}", definition.short_name)),
+            }),
+            definition,
+        }
+    }
+
+    fn contains(&self, player: &Player) -> bool {
+        let definition = &self.definition;
+        definition
+            .divisions
+            .iter()
+            .any(|div| div == &player.division.short_name || div == &player.division.name)
+            || player
+                .pdga_num
+                .is_some_and(|pdga| definition.pdga_numbers.contains(&pdga))
+            || definition
+                .names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&player.name))
+    }
+}
+
+impl VirtualDivisions {
+    /// Reads [`VIRTUAL_DIVISIONS_FILE`], if there is one. Definitions that reuse a short name are skipped
+    pub fn load() -> Self {
+        let divisions = Self::default();
+        let definitions: Vec<dto::VirtualDivision> = load_json_file(VIRTUAL_DIVISIONS_FILE);
+        for definition in definitions {
+            if let Err(e) = divisions.register(definition) {
+                warn!("Skipping a virtual division in {VIRTUAL_DIVISIONS_FILE}: {e}");
+            }
+        }
+        divisions
+    }
+
+    pub fn divisions(&self) -> Vec<Arc<Division>> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|div| div.division.clone())
+            .collect()
+    }
+
+    /// Adds the virtual division, replacing any with the same name.
+    /// Another virtual division can not have the same short name
+    pub fn register(&self, definition: dto::VirtualDivision) -> Result<Arc<Division>, Error> {
+        let mut divisions = self.0.write().unwrap();
+        if divisions.iter().any(|div| {
+            div.definition.short_name == definition.short_name
+                && div.definition.name != definition.name
+        }) {
+            return Err(Error::DuplicateShortName(definition.short_name));
+        }
+        let virtual_division = VirtualDivision::new(definition);
+        let division = virtual_division.division.clone();
+        divisions.retain(|div| div.definition.name != virtual_division.definition.name);
+        divisions.push(virtual_division);
+        Ok(division)
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Arc<Division>> {
+        let mut divisions = self.0.write().unwrap();
+        let index = divisions
+            .iter()
+            .position(|div| div.definition.name == name)?;
+        Some(divisions.remove(index).division)
+    }
+
+    pub fn definitions(&self) -> Vec<dto::VirtualDivision> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|div| div.definition.clone())
+            .collect()
+    }
+
+    pub fn is_virtual(&self, division: &Division) -> bool {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .any(|div| div.division.id == division.id)
+    }
+
+    /// Whether the player should be on the leaderboard of the division, virtual or not
+    pub fn contains(&self, division: &Division, player: &Player) -> bool {
+        player.division.name == division.name
+            || self
+                .0
+                .read()
+                .unwrap()
+                .iter()
+                .find(|div| div.division.id == division.id)
+                .is_some_and(|div| div.contains(player))
+    }
+
+    /// Writes [`VIRTUAL_DIVISIONS_FILE`] in the background
+    pub fn save(&self) {
+        save_json_file(VIRTUAL_DIVISIONS_FILE, &self.definitions());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(name: &str, short_name: &str) -> dto::VirtualDivision {
+        dto::VirtualDivision {
+            name: name.to_string(),
+            short_name: short_name.to_string(),
+            divisions: vec![],
+            pdga_numbers: vec![],
+            names: vec![],
+        }
+    }
+
+    #[test]
+    fn short_names_are_unique() {
+        let divisions = VirtualDivisions::default();
+        divisions.register(definition("Juniors", "JR")).unwrap();
+        assert!(divisions
+            .register(definition("Junior girls", "JR"))
+            .is_err());
+        assert_eq!(divisions.definitions().len(), 1);
+    }
+
+    #[test]
+    fn same_name_replaces() {
+        let divisions = VirtualDivisions::default();
+        let old = divisions.register(definition("Juniors", "JR")).unwrap();
+        let new = divisions.register(definition("Juniors", "JUN")).unwrap();
+        assert_eq!(divisions.definitions().len(), 1);
+        assert!(!divisions.is_virtual(&old));
+        assert!(divisions.is_virtual(&new));
+    }
+
+    #[test]
+    fn clones_share_divisions() {
+        let divisions = VirtualDivisions::default();
+        let leaderboard_copy = divisions.clone();
+        let division = divisions.register(definition("Overall", "ALL")).unwrap();
+        assert!(leaderboard_copy.is_virtual(&division));
+        divisions.remove("Overall");
+        assert!(!leaderboard_copy.is_virtual(&division));
+    }
+}
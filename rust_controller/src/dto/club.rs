@@ -0,0 +1,22 @@
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct ClubStanding {
+    pub position: usize,
+    pub tied: bool,
+    pub club: String,
+    /// Sum of the counting total scores, relative to par
+    pub score: isize,
+    /// False when the club has fewer players left than the amount of counting scores
+    pub complete: bool,
+    /// The players whose scores count, best first
+    pub players: Vec<ClubPlayerScore>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct ClubPlayerScore {
+    pub name: String,
+    pub short_name: String,
+    pub score: isize,
+}
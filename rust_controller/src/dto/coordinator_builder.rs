@@ -1,5 +1,8 @@
 use crate::api::Error;
 use crate::controller::coordinator::{BroadcastType, FlipUpVMixCoordinator};
+use crate::dto::VMixTarget;
+use crate::flipup_vmix_controls::DEFAULT_PAGE_SIZE;
+use crate::vmix::VMixQueue;
 use itertools::Itertools;
 use rocket::serde::json::Json;
 use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
@@ -10,11 +13,19 @@ use tokio::sync::Mutex;
 
 #[derive(Default, Deserialize, JsonSchema, FromForm)]
 pub struct CoordinatorBuilder {
+    /// The main vMix, left out if empty
     ip: String,
+    /// vMix machines next to the main one, like a backup or a replay machine
+    #[serde(default)]
+    vmix_targets: Vec<VMixTarget>,
+    /// Nothing is sent to vMix, commands are only written to the journal
+    #[serde(default)]
+    dry_run: bool,
     event_ids: Vec<String>,
     round: usize,
     featured_hole: u8,
     broadcast_type: BroadcastType,
+    leaderboard_page_size: Option<usize>,
 }
 
 impl CoordinatorBuilder {
@@ -31,6 +42,9 @@ impl CoordinatorBuilder {
             round,
             featured_hole,
             broadcast_type,
+            vmix_targets: vec![],
+            dry_run: false,
+            leaderboard_page_size: None,
         }
     }
 }
@@ -45,13 +59,32 @@ impl CoordinatorBuilder {
                 .collect::<String>(),
         )
         .unwrap();
+        let mut targets = self.vmix_targets;
+        if !self.ip.is_empty() {
+            targets.insert(
+                0,
+                VMixTarget {
+                    name: "main".to_string(),
+                    ip: self.ip,
+                    backend: Default::default(),
+                    graphics: vec![],
+                    exclude: vec![],
+                },
+            );
+        }
+        if targets.is_empty() && !self.dry_run {
+            return Err(Error::NoVMixTargets);
+        }
         FlipUpVMixCoordinator::new(
-            self.ip,
+            VMixQueue::new(targets, self.dry_run)?,
             self.event_ids,
             0,
             self.round,
             self.featured_hole,
             self.broadcast_type,
+            self.leaderboard_page_size
+                .filter(|size| *size > 0)
+                .unwrap_or(DEFAULT_PAGE_SIZE),
         )
         .await
     }
@@ -0,0 +1,49 @@
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HighlightKind {
+    Ace,
+    Albatross,
+    Eagle,
+    LongPutt,
+    BirdieStreak { streak: usize },
+    BogeyFreeRound,
+    RoundLow { score: isize },
+    LeadChange { previous_leader: Option<String> },
+    TiedForLead { players: usize },
+}
+
+impl HighlightKind {
+    /// Same as the `kind` in JSON
+    pub fn name(&self) -> &'static str {
+        match self {
+            HighlightKind::Ace => "ace",
+            HighlightKind::Albatross => "albatross",
+            HighlightKind::Eagle => "eagle",
+            HighlightKind::LongPutt => "long_putt",
+            HighlightKind::BirdieStreak { .. } => "birdie_streak",
+            HighlightKind::BogeyFreeRound => "bogey_free_round",
+            HighlightKind::RoundLow { .. } => "round_low",
+            HighlightKind::LeadChange { .. } => "lead_change",
+            HighlightKind::TiedForLead { .. } => "tied_for_lead",
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct Highlight {
+    /// Position in the list of all highlights, usable as `since` when polling
+    pub id: usize,
+    pub player_id: String,
+    pub player_name: String,
+    pub division: String,
+    pub round: usize,
+    pub hole: Option<u8>,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub kind: HighlightKind,
+    pub description: String,
+}
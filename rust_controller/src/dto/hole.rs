@@ -0,0 +1,34 @@
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct HoleHistory {
+    pub layout: String,
+    pub hole: u8,
+    pub division: String,
+    pub event_name: String,
+    /// Average to par over every round of the current event, with one decimal
+    pub event_average: Option<f64>,
+    pub event_results: usize,
+    /// 1 is the hardest hole of the event
+    pub event_hardest_rank: Option<usize>,
+    /// Average to par over every stored event on the layout, with one decimal
+    pub all_time_average: Option<f64>,
+    pub all_time_results: usize,
+    /// Amount of stored events with results on the hole
+    pub events: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HoleMetadata {
+    pub hole: u8,
+    /// Meters from tee to basket, negative when the basket is lower
+    pub elevation: Option<i16>,
+    /// Path to the hole map image shown on the drone title
+    pub hole_map: Option<String>,
+    pub name: Option<String>,
+    pub tee: Option<String>,
+    pub basket: Option<String>,
+    /// Only for the commentators, never shown on a title
+    pub notes: Option<String>,
+}
@@ -0,0 +1,47 @@
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct LeaderboardPage {
+    pub page: usize,
+    pub pages: usize,
+    pub page_size: usize,
+    pub division: String,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct LeaderboardCycleState {
+    pub interval_seconds: u64,
+    pub order: Vec<String>,
+    pub excluded: Vec<String>,
+    pub paused: bool,
+    pub current: Option<String>,
+    pub featured: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct RoundSummary {
+    pub division: String,
+    pub round: usize,
+    pub players: usize,
+    pub finished_players: usize,
+    /// Lowest round so far, the player may still be playing
+    pub low_round: Option<RoundLow>,
+    /// Average of the finished rounds, with one decimal
+    pub average_round_score: Option<f64>,
+    pub bogey_free_rounds: usize,
+    pub easiest_hole: Option<u8>,
+    pub hardest_hole: Option<u8>,
+    pub aces: usize,
+    pub eagles: usize,
+    pub leader: Option<String>,
+    /// Strokes between the leader and second place, 0 when tied
+    pub leader_margin: Option<isize>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct RoundLow {
+    pub player: String,
+    pub score: isize,
+    pub holes_played: u8,
+}
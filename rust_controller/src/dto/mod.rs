@@ -1,14 +1,31 @@
 use serde::Serialize;
 
+pub use club::{ClubPlayerScore, ClubStanding};
 pub use coordinator_builder::CoordinatorBuilder;
 pub use group::Group;
+pub use highlight::{Highlight, HighlightKind};
+pub use hole::{HoleHistory, HoleMetadata};
+pub use leaderboard::{LeaderboardCycleState, LeaderboardPage, RoundLow, RoundSummary};
 pub use player::*;
 pub use rounds::SimpleRound;
+pub use theme::{Theme, Themes};
+pub use virtual_division::VirtualDivision;
+pub use vmix::{
+    AutoHideAction, AutoHideRule, GraphicsBackend, JournalEntry, ReplayEvent, ReplaySettings,
+    VMixConnection, VMixConnectionState, VMixTarget,
+};
 
+mod club;
 mod coordinator_builder;
 mod group;
+mod highlight;
+mod hole;
+mod leaderboard;
 mod player;
 mod rounds;
+mod theme;
+mod virtual_division;
+mod vmix;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Division {
@@ -1,5 +1,6 @@
 use crate::controller::coordinator::FlipUpVMixCoordinator;
 use crate::controller::queries::Division;
+use crate::controller::stats::StatScope;
 use crate::{controller, dto};
 use itertools::Itertools;
 use rocket_okapi::okapi::schemars;
@@ -16,9 +17,42 @@ pub struct Player {
     pub holes_finished: usize,
     pub index: usize,
     pub queue: Option<usize>,
+    pub stats: PlayerStatistics,
     #[serde(skip)]
     pub division: Arc<Division>,
 }
+
+/// Percentages are left out when there is nothing to base them on yet
+#[derive(Serialize, Debug, JsonSchema, Clone, Default)]
+pub struct PlayerStats {
+    pub holes: usize,
+    pub c1x_putting: Option<u8>,
+    /// Made C2 putts of the holes without C1 in regulation. Tjing does not say which C2 putts were attempted
+    pub c2_made_without_c1_regulation: Option<u8>,
+    pub c1_regulation: Option<u8>,
+    pub c2_regulation: Option<u8>,
+    pub scramble: Option<u8>,
+    pub out_of_bounds: Option<u8>,
+    pub birdie: Option<u8>,
+    pub bogey_avoidance: Option<u8>,
+}
+
+#[derive(Serialize, Debug, JsonSchema, Clone, Default)]
+pub struct PlayerStatistics {
+    pub round: PlayerStats,
+    pub event: PlayerStats,
+    pub previous_rounds: PlayerStats,
+}
+
+impl From<&controller::Player> for PlayerStatistics {
+    fn from(player: &controller::Player) -> Self {
+        Self {
+            round: player.stats(StatScope::Round).to_dto(),
+            event: player.stats(StatScope::Event).to_dto(),
+            previous_rounds: player.stats(StatScope::PreviousRounds).to_dto(),
+        }
+    }
+}
 #[derive(Debug, JsonSchema, Clone, FromForm, Deserialize)]
 pub struct HoleSetting {
     pub hole: Option<u8>,
@@ -43,6 +77,7 @@ impl Player {
             holes_finished,
             index,
             queue,
+            stats: PlayerStatistics::default(),
             division,
         }
     }
@@ -56,6 +91,7 @@ impl Player {
             holes_finished: player.amount_of_holes_finished(),
             index: player.group_index,
             queue,
+            stats: PlayerStatistics::from(&player),
             division: player.division.clone(),
         }
     }
@@ -70,6 +106,7 @@ impl From<&controller::Player> for self::Player {
             holes_finished: player.amount_of_holes_finished(),
             index: player.group_index,
             queue: None,
+            stats: PlayerStatistics::from(player),
             division: player.division.clone(),
         }
     }
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+
+/// Colours of the titles. Colours are hex without `#`, `RRGGBB` or `RRGGBBAA`
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(default)]
+pub struct Theme {
+    pub scores: ScoreColours,
+    /// Colour behind scores, empty score fields are a transparent version of it
+    pub foreground: String,
+    pub background: String,
+    /// Accent colours by division name or short name, e.g. `{"MPO": "E4572EFF"}`
+    pub divisions: BTreeMap<String, String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            scores: ScoreColours::default(),
+            foreground: "3F334D".to_string(),
+            background: "574B60".to_string(),
+            divisions: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(default)]
+pub struct ScoreColours {
+    pub ace: String,
+    pub albatross: String,
+    pub eagle: String,
+    pub birdie: String,
+    pub par: String,
+    pub bogey: String,
+    pub double_bogey: String,
+    pub triple_bogey: String,
+    /// Four or more over par
+    pub ouch: String,
+}
+
+impl Default for ScoreColours {
+    fn default() -> Self {
+        Self {
+            ace: "DD6AC9FF".to_string(),
+            albatross: "DD6AC9FF".to_string(),
+            eagle: "6A8BE7FF".to_string(),
+            birdie: "A6F8BBFF".to_string(),
+            par: "7E8490FF".to_string(),
+            bogey: "EC928FFF".to_string(),
+            double_bogey: "CA988DFF".to_string(),
+            triple_bogey: "AB8E77FF".to_string(),
+            ouch: "AB8E77FF".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct Themes {
+    pub active: String,
+    pub themes: BTreeMap<String, Theme>,
+}
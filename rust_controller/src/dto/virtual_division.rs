@@ -0,0 +1,19 @@
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+
+/// A division made up of players from other divisions.
+/// A player is a member if any of the lists matches them.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct VirtualDivision {
+    pub name: String,
+    /// Shown on the leaderboard title instead of the division name
+    pub short_name: String,
+    /// Names or short names of divisions whose players are all included, e.g. `["MPO", "MA1"]`
+    #[serde(default)]
+    pub divisions: Vec<String>,
+    #[serde(default)]
+    pub pdga_numbers: Vec<u32>,
+    /// Full player names, for players without a PDGA number
+    #[serde(default)]
+    pub names: Vec<String>,
+}
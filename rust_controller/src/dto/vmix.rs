@@ -0,0 +1,133 @@
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VMixConnectionState {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct VMixConnection {
+    pub name: String,
+    pub ip: String,
+    pub state: VMixConnectionState,
+    /// Unix timestamp in seconds of the last time the connection was made
+    pub connected_since: Option<i64>,
+    pub reconnects: usize,
+    pub last_error: Option<String>,
+    /// Fields that are sent again after reconnecting
+    pub remembered_fields: usize,
+}
+
+/// A command in the vMix journal
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct JournalEntry {
+    pub timestamp: String,
+    /// The API call, like `POST /vmix/clear/leaderboard`, or the loop that made the command
+    pub cause: String,
+    /// Graphic name like in the title mapping
+    pub graphic: String,
+    /// vMix targets the command is sent to
+    pub targets: Vec<String>,
+    pub command: String,
+}
+
+#[derive(Deserialize, JsonSchema, FromFormField, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphicsBackend {
+    #[default]
+    VMix,
+    #[field(value = "casparcg")]
+    CasparCG,
+}
+
+/// A vMix machine to send graphics to.
+/// Graphics are named like in the title mapping, e.g. `LeaderBoardProperty` or `DroneHoleInfo`
+#[derive(Deserialize, JsonSchema, FromForm, Debug, Clone)]
+pub struct VMixTarget {
+    pub name: String,
+    /// Can include a port, like `127.0.0.1:8100`
+    pub ip: String,
+    /// A CasparCG server gets its templates from `casparcg_templates.json`
+    #[serde(default)]
+    #[field(default = GraphicsBackend::VMix)]
+    pub backend: GraphicsBackend,
+    /// Only these graphics are sent to the target, every graphic if empty
+    #[serde(default)]
+    pub graphics: Vec<String>,
+    /// Graphics never sent to the target
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Actions that can take a graphic off air again by themselves
+#[derive(
+    Serialize, Deserialize, JsonSchema, FromFormField, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoHideAction {
+    #[field(value = "increase_score")]
+    IncreaseScore,
+    #[field(value = "set_hole_info")]
+    SetHoleInfo,
+    #[field(value = "club_standings")]
+    ClubStandings,
+    #[field(value = "round_summary")]
+    RoundSummary,
+}
+
+/// Input taken in on an overlay channel when the action runs, and out again after `seconds`
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct AutoHideRule {
+    /// Overlay channel, 1 to 4
+    pub overlay: u8,
+    /// GUID, key, number or name of the input, or a title in the title mapping
+    pub input: String,
+    pub seconds: f64,
+}
+
+/// When replay events are marked in vMix Replay
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ReplaySettings {
+    /// Seconds of recording before the mark that the event starts at
+    pub pre_roll: u32,
+    /// Pre-roll of highlights marked automatically. They are marked when the score is entered,
+    /// which can be minutes after the shot, so these events are only rough pointers
+    #[serde(default = "default_automatic_pre_roll")]
+    pub automatic_pre_roll: u32,
+    /// Highlight kinds that are marked, like `ace` or `long_putt`
+    pub kinds: Vec<String>,
+}
+
+fn default_automatic_pre_roll() -> u32 {
+    120
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            pre_roll: 20,
+            automatic_pre_roll: default_automatic_pre_roll(),
+            kinds: ["ace", "albatross", "eagle", "long_putt"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+/// A replay event marked in vMix Replay
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct ReplayEvent {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub player_name: Option<String>,
+    pub hole: Option<u8>,
+    /// Highlight kind, or what the operator called it
+    pub label: String,
+    /// Text of the event in vMix Replay
+    pub text: String,
+    pub pre_roll: u32,
+}
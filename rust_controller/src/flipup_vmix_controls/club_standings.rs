@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use itertools::Itertools;
+
+use crate::controller::fix_score;
+use crate::controller::queries::Division;
+use crate::controller::Player;
+use crate::dto;
+use crate::flipup_vmix_controls::LeaderboardState;
+use crate::vmix::functions::{VMixInterfacer, VMixSelectionTrait};
+
+/// CSV with one `pdga_number,club` pair per line, read when a coordinator is made
+pub const CLUBS_FILE: &str = "clubs.csv";
+
+/// Amount of scores per club that count unless told otherwise
+pub const DEFAULT_COUNTING_SCORES: usize = 3;
+
+/// Amount of rows on the club standings title
+const CLUB_STANDINGS_ROWS: usize = 10;
+
+/// Which club every player represents, keyed by PDGA number
+#[derive(Debug, Clone, Default)]
+pub struct ClubAffiliations(HashMap<u32, String>);
+
+impl ClubAffiliations {
+    pub fn load() -> Self {
+        match std::fs::read_to_string(Path::new(CLUBS_FILE)) {
+            Ok(contents) => Self::parse(&contents),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Accepts both `,` and `;` as separator. Lines that do not start with a PDGA number,
+    /// like a header, are skipped.
+    fn parse(contents: &str) -> Self {
+        let clubs = contents
+            .lines()
+            .filter_map(|line| {
+                let (pdga, club) = line.split_once([',', ';'])?;
+                let pdga = pdga.trim().trim_matches('"').parse::<u32>().ok()?;
+                let club = club.trim().trim_matches('"');
+                if club.is_empty() {
+                    None
+                } else {
+                    Some((pdga, club.to_string()))
+                }
+            })
+            .collect();
+        Self(clubs)
+    }
+
+    pub fn club(&self, pdga_num: u32) -> Option<&str> {
+        self.0.get(&pdga_num).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClubStandings {
+    division: String,
+    round: usize,
+    rows: Vec<dto::ClubStanding>,
+}
+
+impl ClubStandings {
+    /// Ranks the clubs by the sum of their best `counting` total scores in the division.
+    ///
+    /// Clubs with fewer than `counting` players still in the event are ranked after the full ones.
+    pub fn new(
+        state: &LeaderboardState,
+        division: &Division,
+        clubs: &ClubAffiliations,
+        counting: usize,
+    ) -> Self {
+        let by_club = state
+            .players_in_division(division)
+            .into_iter()
+            .filter(|player| !(player.dnf || player.dns))
+            .filter_map(|player| Some((clubs.club(player.pdga_num?)?, player)))
+            .into_group_map();
+
+        let mut rows = by_club
+            .into_iter()
+            .map(|(club, players)| {
+                let players = players
+                    .into_iter()
+                    .sorted_by_key(|player| player.total_score)
+                    .take(counting)
+                    .map(|player| dto::ClubPlayerScore {
+                        name: player.name.clone(),
+                        short_name: Self::short_name(player),
+                        score: player.total_score,
+                    })
+                    .collect_vec();
+                dto::ClubStanding {
+                    position: 0,
+                    tied: false,
+                    club: club.to_string(),
+                    score: players.iter().map(|player| player.score).sum(),
+                    complete: players.len() == counting,
+                    players,
+                }
+            })
+            .sorted_by(|a, b| (!a.complete, a.score, &a.club).cmp(&(!b.complete, b.score, &b.club)))
+            .collect_vec();
+
+        let keys = rows
+            .iter()
+            .map(|row| (row.complete, row.score))
+            .collect_vec();
+        for (index, row) in rows.iter_mut().enumerate() {
+            let key = keys[index];
+            row.position = keys.iter().position(|other| *other == key).unwrap() + 1;
+            row.tied = keys.iter().filter(|other| **other == key).count() > 1;
+        }
+
+        Self {
+            division: division.short_name.clone(),
+            round: state.round(),
+            rows,
+        }
+    }
+
+    fn short_name(player: &Player) -> String {
+        match player.first_name.chars().next() {
+            Some(initial) => format!("{initial}. {}", player.surname),
+            None => player.surname.clone(),
+        }
+    }
+
+    pub fn rows(&self) -> &[dto::ClubStanding] {
+        &self.rows
+    }
+
+    pub fn to_vmix_instructions(&self) -> Vec<VMixInterfacer<ClubStandingsProperty>> {
+        let mut instructions = vec![VMixInterfacer::set_text(
+            format!(
+                "{} | Club standings | Round {}",
+                self.division,
+                self.round + 1
+            ),
+            ClubStandingsProperty::Title,
+        )];
+        for pos in 1..=CLUB_STANDINGS_ROWS {
+            let row = self.rows.get(pos - 1);
+            instructions.extend([
+                VMixInterfacer::set_text(
+                    row.map(|row| {
+                        if row.tied {
+                            format!("T{}", row.position)
+                        } else {
+                            row.position.to_string()
+                        }
+                    })
+                    .unwrap_or_default(),
+                    ClubStandingsProperty::Position(pos),
+                ),
+                VMixInterfacer::set_text(
+                    row.map(|row| row.club.clone()).unwrap_or_default(),
+                    ClubStandingsProperty::Club(pos),
+                ),
+                VMixInterfacer::set_text(
+                    row.map(|row| fix_score(row.score)).unwrap_or_default(),
+                    ClubStandingsProperty::Score(pos),
+                ),
+                VMixInterfacer::set_text(
+                    row.map(|row| {
+                        row.players
+                            .iter()
+                            .map(|player| player.short_name.as_str())
+                            .join(", ")
+                    })
+                    .unwrap_or_default(),
+                    ClubStandingsProperty::Players(pos),
+                ),
+            ]);
+        }
+        instructions
+    }
+}
+
+pub enum ClubStandingsProperty {
+    Title,
+    Position(usize),
+    Club(usize),
+    Score(usize),
+    Players(usize),
+}
+
+impl VMixSelectionTrait for ClubStandingsProperty {
+    const GRAPHIC_NAME: &'static str = "ClubStandingsProperty";
+
+    fn get_selection_name(&self) -> String {
+        match self {
+            ClubStandingsProperty::Title => "title".to_string(),
+            ClubStandingsProperty::Position(pos) => format!("pos#{pos}"),
+            ClubStandingsProperty::Club(pos) => format!("club#{pos}"),
+            ClubStandingsProperty::Score(pos) => format!("score#{pos}"),
+            ClubStandingsProperty::Players(pos) => format!("players#{pos}"),
+        }
+    }
+
+    fn data_extension(&self) -> &'static str {
+        "Text"
+    }
+
+    fn value(&self) -> Option<String> {
+        None
+    }
+
+    /// No title in the default project, the input comes from the title mapping
+    fn input_id(&self) -> &'static str {
+        ""
+    }
+}
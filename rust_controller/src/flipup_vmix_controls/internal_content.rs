@@ -1,3 +1,5 @@
+use crate::assets;
+
 pub enum Image {
     Nothing,
     GreenTriUp,
@@ -6,13 +8,26 @@ pub enum Image {
 }
 
 impl Image {
+    fn file(&self) -> &'static str {
+        match self {
+            Image::Nothing => "alpha.png",
+            Image::GreenTriUp => "greentri.png",
+            Image::RedTriDown => "redtri.png",
+            Image::Flames => "fire.png",
+        }
+    }
+
     pub fn to_location(&self) -> String {
-        String::from("C:\\livegrafik-flipup\\images\\gfx\\")
-            + match self {
-                Image::Nothing => "alpha.png",
-                Image::GreenTriUp => "greentri.png",
-                Image::RedTriDown => "redtri.png",
-                Image::Flames => "fire.png",
-            }
+        assets::get().gfx(self.file())
+    }
+
+    /// For the hot round of the leaderboard title
+    pub fn to_hot_round_location(&self) -> String {
+        assets::get().hot_round_image(self.file())
+    }
+
+    /// For the position arrows of the leaderboard property title
+    pub fn to_arrow_location(&self) -> String {
+        assets::get().arrow_image(self.file())
     }
 }
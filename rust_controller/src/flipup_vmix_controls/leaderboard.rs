@@ -8,25 +8,50 @@ use crate::controller::coordinator::BroadcastType;
 use crate::controller::fix_score;
 use crate::controller::get_data::HoleResult;
 use crate::controller::queries::Division;
+use crate::controller::virtual_division::VirtualDivisions;
 use crate::controller::Player;
+use crate::dto;
 use crate::flipup_vmix_controls::leaderboard::prop::FeaturedLeaderboard;
+use crate::flipup_vmix_controls::theme::Themes;
 use crate::flipup_vmix_controls::Image;
 use crate::vmix::functions::{VMixInterfacer, VMixSelectionTrait};
 use crate::vmix::VMixQueue;
 pub use prop::{CycledLeaderboard, LeaderBoardProperty, LeaderboardTop6};
 
-#[derive(Debug, Clone, Default)]
+/// Amount of rows shown on the big leaderboard title unless told otherwise.
+pub const DEFAULT_PAGE_SIZE: usize = 10;
+
+#[derive(Debug, Clone)]
 pub struct Leaderboard {
     states: Vec<LeaderboardState>,
     pub skip: usize,
     pub cycle: bool,
+    pub page_size: usize,
     broadcast_type: Arc<BroadcastType>,
+    virtual_divisions: VirtualDivisions,
+    themes: Themes,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self {
+            states: vec![],
+            skip: 0,
+            cycle: false,
+            page_size: DEFAULT_PAGE_SIZE,
+            broadcast_type: Default::default(),
+            virtual_divisions: Default::default(),
+            themes: Default::default(),
+        }
+    }
 }
 #[derive(Debug, Clone)]
 pub struct LeaderboardState {
     where_to_start: LeaderboardStart,
     round: usize,
     players: Vec<Player>,
+    /// Those of the leaderboard the state is in
+    virtual_divisions: VirtualDivisions,
 }
 #[derive(Debug, Clone)]
 pub enum LeaderboardStart {
@@ -44,6 +69,11 @@ impl Leaderboard {
             .unwrap()
             .leaderboard_players(&division, self.previous_state(round))
     }
+    /// The state that is shown for the round
+    pub fn state(&self, round: usize) -> Option<&LeaderboardState> {
+        self.current_state(round)
+    }
+
     fn current_state(&self, round: usize) -> Option<&LeaderboardState> {
         let state = match self.broadcast_type.as_ref() {
             BroadcastType::PostLive => self.find_state_by_round(round),
@@ -69,7 +99,26 @@ impl Leaderboard {
         }
     }
 
-    pub fn update_players(&mut self, new_state: LeaderboardState) {
+    /// The virtual divisions of the coordinator, for this and every state added after
+    pub fn set_virtual_divisions(&mut self, virtual_divisions: VirtualDivisions) {
+        for state in &mut self.states {
+            state.virtual_divisions = virtual_divisions.clone();
+        }
+        self.virtual_divisions = virtual_divisions;
+    }
+
+    /// The themes of the coordinator, so the colours follow the theme in use
+    pub fn set_themes(&mut self, themes: Themes) {
+        self.themes = themes;
+    }
+
+    /// The theme in use
+    pub fn theme(&self) -> Arc<dto::Theme> {
+        self.themes.active()
+    }
+
+    pub fn update_players(&mut self, mut new_state: LeaderboardState) {
+        new_state.virtual_divisions = self.virtual_divisions.clone();
         if let Some(state) = self
             .states
             .iter_mut()
@@ -91,18 +140,66 @@ impl Leaderboard {
         self.current_state(round)
             .map(|state| {
                 state.send_to_vmix(
+                    &self.theme(),
                     self.cycle,
                     division,
                     self.previous_state(round),
                     queue.clone(),
                     self.skip,
+                    self.page_size,
                     featured,
                 )
             })
             .expect("Should work")
     }
 
-    pub fn add_state(&mut self, state: LeaderboardState) {
+    /// The big leaderboard functions of the current page
+    pub fn page_funcs(
+        &self,
+        division: &Division,
+        round: usize,
+    ) -> Vec<VMixInterfacer<LeaderBoardProperty>> {
+        self.current_state(round)
+            .map(|state| {
+                state.big_leaderboard_funcs(
+                    &self.theme(),
+                    division,
+                    self.previous_state(round),
+                    self.skip,
+                    self.page_size,
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Amount of pages needed to show every player in the division, never less than one.
+    pub fn page_count(&self, division: &Division, round: usize) -> usize {
+        let Some(state) = self.current_state(round) else {
+            return 1;
+        };
+        let players = state
+            .leaderboard_players(division, self.previous_state(round))
+            .len();
+        players.div_ceil(self.page_size.max(1)).max(1)
+    }
+
+    /// Zero-indexed page that the player is shown on in the division leaderboard
+    pub fn page_of_player(
+        &self,
+        player_id: &str,
+        division: &Division,
+        round: usize,
+    ) -> Option<usize> {
+        let state = self.current_state(round)?;
+        state
+            .leaderboard_players(division, self.previous_state(round))
+            .into_iter()
+            .position(|lb_player| lb_player.id == player_id)
+            .map(|row| row / self.page_size.max(1))
+    }
+
+    pub fn add_state(&mut self, mut state: LeaderboardState) {
+        state.virtual_divisions = self.virtual_divisions.clone();
         if self
             .current_state(self.states.len().checked_sub(1).unwrap_or_default())
             .is_some_and(|current_state| current_state.round == state.round)
@@ -137,8 +234,11 @@ impl Leaderboard {
                 Some(round) => self.previous_state(round),
                 None => None,
             };
-            let previous_batch = current.big_leaderboard_funcs(div, previous, 0);
+            let theme = self.theme();
+            let previous_batch =
+                current.big_leaderboard_funcs(&theme, div, previous, 0, self.page_size);
             current.update_little_leaderboard::<CycledLeaderboard>(
+                &theme,
                 div,
                 previous_batch,
                 previous,
@@ -177,6 +277,7 @@ impl LeaderboardState {
             where_to_start: LeaderboardStart::Latest,
             round,
             players: current_round_players,
+            virtual_divisions: VirtualDivisions::default(),
         }
     }
 
@@ -217,30 +318,44 @@ impl LeaderboardState {
             .min()
             .unwrap_or_default();
         let other = other.map(|state| state.leaderboard_players(division, None));
-        let players_with_pos = Self::players_with_positions(
-            self.players
-                .iter()
-                .filter(|player| player.division.name == division.name)
-                .collect_vec(),
-        );
+        let members = self.players_in_division(division);
+        // Players of several divisions share the board, so show which one they are in
+        let show_badges = self.virtual_divisions.is_virtual(division);
+        let players_with_pos = Self::players_with_positions(members.clone());
 
         players_with_pos
             .into_iter()
             .enumerate()
             .map(|(real_pos, (index, player))| {
-                LeaderboardPlayer::new(
+                let mut lb_player = LeaderboardPlayer::new(
                     player,
                     index,
                     real_pos + 1,
                     min_score,
                     other.as_ref(),
                     self.round,
-                    &self.players,
-                )
+                    &members,
+                );
+                if show_badges {
+                    lb_player.division_badge = Some(player.division.short_name.clone());
+                }
+                lb_player
             })
             .collect_vec()
     }
 
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Players on the leaderboard of the division, best first
+    pub fn players_in_division(&self, division: &Division) -> Vec<&Player> {
+        self.players
+            .iter()
+            .filter(|player| self.virtual_divisions.contains(division, player))
+            .collect_vec()
+    }
+
     fn players_with_positions(players: Vec<&Player>) -> Vec<(usize, &Player)> {
         let mut pos = 1;
         let mut same_score_count = 0;
@@ -261,16 +376,19 @@ impl LeaderboardState {
             .collect_vec()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn send_to_vmix(
         &self,
+        theme: &dto::Theme,
         cycled: bool,
         division: &Division,
         other: Option<&Self>,
         queue: Arc<VMixQueue>,
         skip: usize,
+        page_size: usize,
         featured: bool,
     ) {
-        let first_batch = self.big_leaderboard_funcs(division, other, skip);
+        let first_batch = self.big_leaderboard_funcs(theme, division, other, skip, page_size);
 
         queue.add_ref(first_batch.iter());
         let func = if featured {
@@ -281,31 +399,43 @@ impl LeaderboardState {
         if skip > 0 {
             func(
                 self,
+                theme,
                 division,
-                self.big_leaderboard_funcs(division, other, 0),
+                self.big_leaderboard_funcs(theme, division, other, 0, page_size),
                 other,
                 queue,
                 cycled,
                 featured,
             )
         } else {
-            func(self, division, first_batch, other, queue, cycled, featured);
+            func(
+                self,
+                theme,
+                division,
+                first_batch,
+                other,
+                queue,
+                cycled,
+                featured,
+            );
         }
     }
 
     pub fn big_leaderboard_funcs(
         &self,
+        theme: &dto::Theme,
         division: &Division,
         other: Option<&Self>,
         skip: usize,
+        page_size: usize,
     ) -> Vec<VMixInterfacer<LeaderBoardProperty>> {
         let mut players = self.leaderboard_players(division, other);
         let mut funcs = players
             .iter_mut()
-            .skip(skip * 10)
-            .take(10)
+            .skip(skip * page_size)
+            .take(page_size)
             .map(|player| {
-                player.index -= skip * 10;
+                player.index -= skip * page_size;
                 &*player
             })
             .flat_map(LeaderboardPlayer::combine)
@@ -314,11 +444,17 @@ impl LeaderboardState {
             format!("{} | Round {}", division.short_name, self.round + 1),
             LeaderBoardProperty::CheckinText,
         ));
+        funcs.push(VMixInterfacer::set_color(
+            theme.accent_or_foreground(division),
+            LeaderBoardProperty::Accent,
+        ));
         funcs
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_little_leaderboard<S>(
         &self,
+        theme: &dto::Theme,
         division: &Division,
         first_batch: Vec<VMixInterfacer<LeaderBoardProperty>>,
         other: Option<&Self>,
@@ -353,8 +489,12 @@ impl LeaderboardState {
                         .iter()
                         .enumerate()
                         .flat_map(|(hole_index, result)| match result {
-                            Some(res) => res.to_leaderboard_top_6(player.index, hole_index + 1),
-                            None => HoleResult::hide_hole_top_6(player.index, hole_index + 1),
+                            Some(res) => {
+                                res.to_leaderboard_top_6(theme, player.index, hole_index + 1)
+                            }
+                            None => {
+                                HoleResult::hide_hole_top_6(theme, player.index, hole_index + 1)
+                            }
                         })
                         .collect_vec(),
                 )
@@ -379,11 +519,12 @@ pub struct LeaderboardPlayer {
     pub position: usize,
     pub movement: LeaderboardMovement,
     pub hot_round: bool,
-    name: String,
+    pub name: String,
     pub round_score: isize,
     pub total_score: isize,
     thru: u8,
     pub tied: Option<u8>,
+    pub division_badge: Option<String>,
     dns: bool,
     dnf: bool,
 }
@@ -400,6 +541,8 @@ impl LeaderboardPlayer {
     /// * `max_score_reached` - The maximum score reached by any player in the round
     ///
     /// * `other_board` - The other leaderboard to compare the movement to
+    ///
+    /// * `all_other_players` - Everyone on the same leaderboard, used to find ties
     pub fn new(
         player: &Player,
         pos: usize,
@@ -407,7 +550,7 @@ impl LeaderboardPlayer {
         min_score_reached: isize,
         other_board: Option<&Vec<LeaderboardPlayer>>,
         round: usize,
-        all_other_players: &[Player],
+        all_other_players: &[&Player],
     ) -> Self {
         let other_pos = other_board
             .and_then(|players| {
@@ -423,7 +566,6 @@ impl LeaderboardPlayer {
         let tie = {
             let tie_count = all_other_players
                 .iter()
-                .filter(|lb_player| lb_player.division.id == player.division.id)
                 .filter(|lb_player| lb_player.total_score == player.total_score)
                 .count();
             if tie_count > 1 {
@@ -443,12 +585,18 @@ impl LeaderboardPlayer {
             total_score: player.total_score,
             thru: player.results.amount_of_holes_finished(),
             tied: tie,
+            division_badge: None,
             id: player.player_id.clone(),
             dns: player.dns,
             dnf: player.dnf,
         }
     }
 
+    /// Holes finished in the round
+    pub fn thru(&self) -> u8 {
+        self.thru
+    }
+
     fn set_hot_round(&self) -> VMixInterfacer<LeaderBoardProperty> {
         VMixInterfacer::set_image(
             if self.hot_round {
@@ -530,6 +678,13 @@ impl LeaderboardPlayer {
         )
     }
 
+    fn set_division_badge(&self) -> VMixInterfacer<LeaderBoardProperty> {
+        VMixInterfacer::set_text(
+            self.division_badge.clone().unwrap_or_default(),
+            LeaderBoardProperty::DivisionBadge(self.index),
+        )
+    }
+
     pub fn combine(&self) -> Vec<VMixInterfacer<LeaderBoardProperty>> {
         vec![
             self.set_hot_round(),
@@ -540,6 +695,7 @@ impl LeaderboardPlayer {
             self.set_movement_text(),
             self.set_thru(),
             self.set_name(),
+            self.set_division_badge(),
         ]
     }
 }
@@ -567,18 +723,31 @@ mod prop {
 
     #[derive(Clone)]
     pub enum LeaderBoardProperty {
-        Position { pos: usize },
+        Position {
+            pos: usize,
+        },
         Name(usize),
         HotRound(usize),
         RoundScore(usize),
-        TotalScore { pos: usize },
-        Move { pos: usize },
-        Arrow { pos: usize },
+        TotalScore {
+            pos: usize,
+        },
+        Move {
+            pos: usize,
+        },
+        Arrow {
+            pos: usize,
+        },
         Thru(usize),
+        DivisionBadge(usize),
         CheckinText,
         TotalScoreTitle,
+        /// Colour of the division in the theme
+        Accent,
     }
     impl VMixSelectionTrait for LeaderBoardProperty {
+        const GRAPHIC_NAME: &'static str = "LeaderBoardProperty";
+
         fn get_selection_name(&self) -> String {
             match self {
                 LeaderBoardProperty::Position { pos, .. } => {
@@ -598,12 +767,15 @@ mod prop {
                     format!("arw{pos}")
                 }
                 LeaderBoardProperty::Thru(pos) => format!("thru#{pos}"),
+                LeaderBoardProperty::DivisionBadge(pos) => format!("div#{pos}"),
                 LeaderBoardProperty::CheckinText => "checkintext".to_string(),
+                LeaderBoardProperty::Accent => "accent".to_string(),
             }
         }
         fn data_extension(&self) -> &'static str {
             match self {
                 LeaderBoardProperty::HotRound(_) | LeaderBoardProperty::Arrow { .. } => "Source",
+                LeaderBoardProperty::Accent => "Fill.Color",
                 _ => "Text",
             }
         }
@@ -619,6 +791,8 @@ mod prop {
     pub struct FeaturedLeaderboard(LeaderboardTop6);
 
     impl VMixSelectionTrait for FeaturedLeaderboard {
+        const GRAPHIC_NAME: &'static str = "FeaturedLeaderboard";
+
         fn get_selection_name(&self) -> String {
             self.0.get_selection_name()
         }
@@ -650,6 +824,8 @@ mod prop {
     pub struct CycledLeaderboard(LeaderboardTop6);
 
     impl VMixSelectionTrait for CycledLeaderboard {
+        const GRAPHIC_NAME: &'static str = "CycledLeaderboard";
+
         fn get_selection_name(&self) -> String {
             self.0.get_selection_name()
         }
@@ -689,8 +865,11 @@ mod prop {
         TotalScore { pos: usize },
         Thru { pos: usize },
         DivisionName,
+        Accent,
     }
     impl VMixSelectionTrait for LeaderboardTop6 {
+        const GRAPHIC_NAME: &'static str = "LeaderboardTop6";
+
         fn get_selection_name(&self) -> String {
             use LeaderboardTop6::*;
             match self {
@@ -702,11 +881,12 @@ mod prop {
                 TotalScore { pos } => format!("p{pos}scoretot"),
                 Thru { pos } => format!("p{pos}thru"),
                 DivisionName => "top6txt".to_string(),
+                Accent => "accent".to_string(),
             }
         }
         fn data_extension(&self) -> &'static str {
             match self {
-                LeaderboardTop6::LastScoreColour { .. } => "Fill.Color",
+                LeaderboardTop6::LastScoreColour { .. } | LeaderboardTop6::Accent => "Fill.Color",
                 _ => "Text",
             }
         }
@@ -725,6 +905,7 @@ mod prop {
                 LeaderBoardProperty::TotalScoreTitle
                 | LeaderBoardProperty::Arrow { .. }
                 | LeaderBoardProperty::HotRound(_)
+                | LeaderBoardProperty::DivisionBadge(_)
                 | LeaderBoardProperty::Move { .. } => None,
                 LeaderBoardProperty::Position { pos } => Some(LeaderboardTop6::Position { pos }),
                 LeaderBoardProperty::Thru(pos) => Some(LeaderboardTop6::Thru { pos }),
@@ -735,6 +916,7 @@ mod prop {
 
                 LeaderBoardProperty::Name(pos) => Some(LeaderboardTop6::Name { pos }),
                 LeaderBoardProperty::CheckinText => Some(LeaderboardTop6::DivisionName),
+                LeaderBoardProperty::Accent => Some(LeaderboardTop6::Accent),
             }
         }
     }
@@ -1,10 +1,17 @@
+pub use club_standings::{
+    ClubAffiliations, ClubStandings, ClubStandingsProperty, DEFAULT_COUNTING_SCORES,
+};
 pub use internal_content::Image;
 pub use leaderboard::{
-    CycledLeaderboard, LeaderBoardProperty, Leaderboard, LeaderboardMovement, LeaderboardState,
-    LeaderboardTop6,
+    CycledLeaderboard, LeaderBoardProperty, Leaderboard, LeaderboardMovement, LeaderboardPlayer,
+    LeaderboardState, LeaderboardTop6, DEFAULT_PAGE_SIZE,
 };
+pub use round_summary::{RoundSummary, RoundSummaryProperty};
 pub use score::{OverarchingScore, Score};
 
+mod club_standings;
 mod internal_content;
 mod leaderboard;
+mod round_summary;
 mod score;
+pub mod theme;
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use crate::controller::fix_score;
+use crate::controller::hole::{HoleDifficulty, HoleStats, ScoreCategory};
+use crate::controller::queries::Division;
+use crate::dto;
+use crate::flipup_vmix_controls::LeaderboardState;
+use crate::vmix::functions::{VMixInterfacer, VMixSelectionTrait};
+
+/// "Round in numbers" for one division
+#[derive(Debug, Clone)]
+pub struct RoundSummary(dto::RoundSummary);
+
+impl RoundSummary {
+    /// Expects the state of the round that should be summarised
+    pub fn new(state: &LeaderboardState, division: Arc<Division>) -> Self {
+        let players = state.players_in_division(&division);
+        let finished = players
+            .iter()
+            .filter(|player| player.results.amount_of_holes_finished() == 18)
+            .collect_vec();
+
+        let low_round = players
+            .iter()
+            .filter(|player| player.results.amount_of_holes_finished() > 0)
+            .min_by_key(|player| player.round_score)
+            .map(|player| dto::RoundLow {
+                player: player.name.clone(),
+                score: player.round_score,
+                holes_played: player.results.amount_of_holes_finished(),
+            });
+
+        let average_round_score = if finished.is_empty() {
+            None
+        } else {
+            let sum = finished
+                .iter()
+                .map(|player| player.round_score as f64)
+                .sum::<f64>();
+            Some((sum / finished.len() as f64 * 10.).round() / 10.)
+        };
+
+        let results = players
+            .iter()
+            .flat_map(|player| player.results.verified_results())
+            .collect_vec();
+        let count = |category: ScoreCategory| {
+            results
+                .iter()
+                .filter(|result| ScoreCategory::new(result.score, result.par) == category)
+                .count()
+        };
+
+        let mut hole_results: HashMap<u8, Vec<_>> = HashMap::new();
+        for result in &results {
+            hole_results
+                .entry(result.hole_number as u8)
+                .or_default()
+                .push((division.clone(), (*result).clone()));
+        }
+        let hole_stats = hole_results
+            .into_iter()
+            .map(|(hole, results)| HoleStats::new(hole, results))
+            .collect_vec();
+        let difficulty =
+            (!hole_stats.is_empty()).then(|| HoleDifficulty::new(hole_stats, &division));
+
+        let leader_margin = match players.as_slice() {
+            [first, second, ..] => Some(second.total_score - first.total_score),
+            _ => None,
+        };
+
+        Self(dto::RoundSummary {
+            division: division.name.clone(),
+            round: state.round() + 1,
+            players: players.len(),
+            finished_players: finished.len(),
+            low_round,
+            average_round_score,
+            bogey_free_rounds: finished
+                .iter()
+                .filter(|player| player.results.is_bogey_free())
+                .count(),
+            easiest_hole: difficulty.as_ref().and_then(HoleDifficulty::easiest),
+            hardest_hole: difficulty.as_ref().and_then(HoleDifficulty::hardest),
+            aces: count(ScoreCategory::Ace),
+            eagles: count(ScoreCategory::Eagle),
+            leader: players.first().map(|player| player.name.clone()),
+            leader_margin,
+        })
+    }
+
+    pub fn to_dto(&self) -> dto::RoundSummary {
+        self.0.clone()
+    }
+
+    pub fn to_vmix_instructions(&self) -> Vec<VMixInterfacer<RoundSummaryProperty>> {
+        let summary = &self.0;
+        let optional = |value: Option<String>| value.unwrap_or("-".to_string());
+        vec![
+            VMixInterfacer::set_text(
+                format!("{} | Round {} in numbers", summary.division, summary.round),
+                RoundSummaryProperty::Title,
+            ),
+            VMixInterfacer::set_text(
+                optional(summary.low_round.as_ref().map(|low| fix_score(low.score))),
+                RoundSummaryProperty::LowRound,
+            ),
+            VMixInterfacer::set_text(
+                optional(summary.low_round.as_ref().map(|low| low.player.clone())),
+                RoundSummaryProperty::LowRoundPlayer,
+            ),
+            VMixInterfacer::set_text(
+                optional(summary.average_round_score.map(|average| {
+                    if average > 0. {
+                        format!("%2B{average:.1}")
+                    } else {
+                        format!("{average:.1}")
+                    }
+                })),
+                RoundSummaryProperty::AverageScore,
+            ),
+            VMixInterfacer::set_text(
+                summary.bogey_free_rounds.to_string(),
+                RoundSummaryProperty::BogeyFreeRounds,
+            ),
+            VMixInterfacer::set_text(
+                optional(summary.easiest_hole.map(|hole| hole.to_string())),
+                RoundSummaryProperty::EasiestHole,
+            ),
+            VMixInterfacer::set_text(
+                optional(summary.hardest_hole.map(|hole| hole.to_string())),
+                RoundSummaryProperty::HardestHole,
+            ),
+            VMixInterfacer::set_text(summary.aces.to_string(), RoundSummaryProperty::Aces),
+            VMixInterfacer::set_text(summary.eagles.to_string(), RoundSummaryProperty::Eagles),
+            VMixInterfacer::set_text(
+                optional(summary.leader.clone()),
+                RoundSummaryProperty::Leader,
+            ),
+            VMixInterfacer::set_text(
+                optional(summary.leader_margin.map(|margin| margin.to_string())),
+                RoundSummaryProperty::LeaderMargin,
+            ),
+        ]
+    }
+}
+
+pub enum RoundSummaryProperty {
+    Title,
+    LowRound,
+    LowRoundPlayer,
+    AverageScore,
+    BogeyFreeRounds,
+    EasiestHole,
+    HardestHole,
+    Aces,
+    Eagles,
+    Leader,
+    LeaderMargin,
+}
+
+impl VMixSelectionTrait for RoundSummaryProperty {
+    const GRAPHIC_NAME: &'static str = "RoundSummaryProperty";
+
+    fn get_selection_name(&self) -> String {
+        match self {
+            RoundSummaryProperty::Title => "title",
+            RoundSummaryProperty::LowRound => "lowround",
+            RoundSummaryProperty::LowRoundPlayer => "lowroundname",
+            RoundSummaryProperty::AverageScore => "avgscore",
+            RoundSummaryProperty::BogeyFreeRounds => "bogeyfree",
+            RoundSummaryProperty::EasiestHole => "easiesthole",
+            RoundSummaryProperty::HardestHole => "hardesthole",
+            RoundSummaryProperty::Aces => "aces",
+            RoundSummaryProperty::Eagles => "eagles",
+            RoundSummaryProperty::Leader => "leadername",
+            RoundSummaryProperty::LeaderMargin => "leadmargin",
+        }
+        .to_string()
+    }
+
+    fn data_extension(&self) -> &'static str {
+        "Text"
+    }
+
+    fn value(&self) -> Option<String> {
+        None
+    }
+
+    /// No title in the default project, the input comes from the title mapping
+    fn input_id(&self) -> &'static str {
+        ""
+    }
+}
@@ -1,4 +1,6 @@
+use crate::assets;
 use crate::controller::fix_score;
+use crate::dto;
 use crate::vmix::functions::{VMixInterfacer, VMixPlayerInfo};
 
 pub struct OverarchingScore {
@@ -95,35 +97,41 @@ impl ReadableScore {
         }
     }
 
-    pub(crate) const fn to_colour(&self) -> &'static str {
+    /// Colour in the theme
+    pub(crate) fn to_colour(&self, theme: &dto::Theme) -> String {
         use ReadableScore::*;
+        let colours = &theme.scores;
         match self {
             Bogey(bogey_type) => match bogey_type {
-                BogeyType::Triple | BogeyType::Ouch => "AB8E77FF",
-                BogeyType::Double => "CA988DFF",
-                BogeyType::Single => "EC928FFF",
+                BogeyType::Ouch => &colours.ouch,
+                BogeyType::Triple => &colours.triple_bogey,
+                BogeyType::Double => &colours.double_bogey,
+                BogeyType::Single => &colours.bogey,
             },
-            Par => "7E8490FF",
-            Birdie => "A6F8BBFF",
-            Eagle => "6A8BE7FF",
-            Ace | Albatross => "DD6AC9FF",
+            Par => &colours.par,
+            Birdie => &colours.birdie,
+            Eagle => &colours.eagle,
+            Albatross => &colours.albatross,
+            Ace => &colours.ace,
         }
+        .clone()
     }
 
-    const fn to_mov(&self) -> &'static str {
+    fn to_mov(&self) -> &'static str {
         use ReadableScore::*;
+        let animations = &assets::get().animations;
         match self {
             Bogey(bogey_type) => match bogey_type {
-                BogeyType::Ouch => "40 ouch.mov",
-                BogeyType::Triple => "30 3xBogey.mov",
-                BogeyType::Double => "20 2xBogey.mov",
-                BogeyType::Single => "10 bogey.mov",
+                BogeyType::Ouch => &animations.ouch,
+                BogeyType::Triple => &animations.triple_bogey,
+                BogeyType::Double => &animations.double_bogey,
+                BogeyType::Single => &animations.bogey,
             },
-            Par => "04 par.mov",
-            Birdie => "03 birdie.mov",
-            Eagle => "02 eagle.mov",
-            Albatross => "01 albatross.mov",
-            Ace => "00 ace.mov",
+            Par => &animations.par,
+            Birdie => &animations.birdie,
+            Eagle => &animations.eagle,
+            Albatross => &animations.albatross,
+            Ace => &animations.ace,
         }
     }
 }
@@ -147,9 +155,13 @@ impl Score {
         self.throws - self.par
     }
 
-    pub fn update_score_colour(&self, player: usize) -> VMixInterfacer<VMixPlayerInfo> {
+    pub fn update_score_colour(
+        &self,
+        theme: &dto::Theme,
+        player: usize,
+    ) -> VMixInterfacer<VMixPlayerInfo> {
         VMixInterfacer::set_color(
-            self.readable_score.to_colour(),
+            &self.readable_score.to_colour(theme),
             VMixPlayerInfo::ScoreColor {
                 hole: self.hole as usize,
                 player,
@@ -157,14 +169,18 @@ impl Score {
         )
     }
 
-    pub fn get_score_colour(&self) -> &'static str {
-        self.readable_score.to_colour()
+    pub fn get_score_colour(&self, theme: &dto::Theme) -> String {
+        self.readable_score.to_colour(theme)
     }
-    pub fn update_score(&self, player: usize) -> [VMixInterfacer<VMixPlayerInfo>; 3] {
+    pub fn update_score(
+        &self,
+        theme: &dto::Theme,
+        player: usize,
+    ) -> [VMixInterfacer<VMixPlayerInfo>; 3] {
         [
             self.update_total_score_text(player),
             self.show_score(player),
-            self.update_score_colour(player),
+            self.update_score_colour(theme, player),
         ]
     }
 
@@ -208,7 +224,7 @@ impl Score {
 
     fn to_vmix_mov(&self, ob: bool) -> VMixInterfacer<VMixPlayerInfo> {
         if ob {
-            VMixInterfacer::overlay_input_4("50 ob.mov")
+            VMixInterfacer::overlay_input_4(&assets::get().animations.ob)
         } else {
             VMixInterfacer::overlay_input_4(self.readable_score.to_mov())
         }
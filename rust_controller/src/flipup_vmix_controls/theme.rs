@@ -0,0 +1,175 @@
+//! Named colour themes for the titles, e.g. one per event or sponsor, read from [`THEMES_FILE`]
+//! when a coordinator is made and switched through the API:
+//!
+//! ```json
+//! {
+//!     "sponsor": {
+//!         "scores": { "birdie": "5FD38DFF", "bogey": "F28C28FF" },
+//!         "foreground": "1B2A41",
+//!         "divisions": { "MPO": "E4572EFF", "FPO": "2E86ABFF" }
+//!     }
+//! }
+//! ```
+//!
+//! Anything left out of a theme keeps the default colour. The `default` theme is always there,
+//! and is the one in use until another is chosen.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use crate::api::Error;
+use crate::controller::queries::Division;
+use crate::dto;
+use crate::json_file::load_json_file;
+
+pub const THEMES_FILE: &str = "themes.json";
+pub const DEFAULT_THEME: &str = "default";
+
+/// The themes of a coordinator. Clones share the themes, so leaderboards use the one chosen
+/// after they were made
+#[derive(Debug, Clone, Default)]
+pub struct Themes(Arc<RwLock<ThemeState>>);
+
+#[derive(Debug)]
+struct ThemeState {
+    themes: BTreeMap<String, Arc<dto::Theme>>,
+    active: String,
+}
+
+impl Default for ThemeState {
+    fn default() -> Self {
+        Self {
+            themes: BTreeMap::from([(DEFAULT_THEME.to_string(), Arc::default())]),
+            active: DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+impl Themes {
+    /// Reads [`THEMES_FILE`], if there is one. Themes with a colour that is not hex are skipped
+    pub fn load() -> Self {
+        let mut state = ThemeState::default();
+        let loaded: BTreeMap<String, dto::Theme> = load_json_file(THEMES_FILE);
+        for (name, theme) in loaded {
+            match theme.validate() {
+                Ok(()) => {
+                    state.themes.insert(name, Arc::new(theme));
+                }
+                Err(e) => warn!("Skipping the theme {name} in {THEMES_FILE}: {e}"),
+            }
+        }
+        Self(Arc::new(RwLock::new(state)))
+    }
+
+    /// The theme in use
+    pub fn active(&self) -> Arc<dto::Theme> {
+        let state = self.0.read().unwrap();
+        state.themes[&state.active].clone()
+    }
+
+    /// Only changes what is sent from now on, see [`crate::controller::coordinator::FlipUpVMixCoordinator::resend_colours`]
+    pub fn set_active(&self, name: &str) -> Result<(), Error> {
+        let mut state = self.0.write().unwrap();
+        if !state.themes.contains_key(name) {
+            return Err(Error::ThemeNotFound(name.to_string()));
+        }
+        state.active = name.to_string();
+        Ok(())
+    }
+
+    pub fn to_dto(&self) -> dto::Themes {
+        let state = self.0.read().unwrap();
+        dto::Themes {
+            active: state.active.clone(),
+            themes: state
+                .themes
+                .iter()
+                .map(|(name, theme)| (name.clone(), dto::Theme::clone(theme)))
+                .collect(),
+        }
+    }
+}
+
+/// `RRGGBB` or `RRGGBBAA`
+fn is_hex_colour(colour: &str) -> bool {
+    matches!(colour.len(), 6 | 8) && colour.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+impl dto::Theme {
+    /// Every colour has to be hex, as they are sliced and sent to vMix as they are
+    fn validate(&self) -> Result<(), String> {
+        let scores = &self.scores;
+        [
+            ("foreground", &self.foreground),
+            ("background", &self.background),
+            ("ace", &scores.ace),
+            ("albatross", &scores.albatross),
+            ("eagle", &scores.eagle),
+            ("birdie", &scores.birdie),
+            ("par", &scores.par),
+            ("bogey", &scores.bogey),
+            ("double_bogey", &scores.double_bogey),
+            ("triple_bogey", &scores.triple_bogey),
+            ("ouch", &scores.ouch),
+        ]
+        .into_iter()
+        .chain(
+            self.divisions
+                .iter()
+                .map(|(division, colour)| (division.as_str(), colour)),
+        )
+        .find(|(_, colour)| !is_hex_colour(colour))
+        .map_or(Ok(()), |(name, colour)| {
+            Err(format!("{name} is {colour:?}, not RRGGBB or RRGGBBAA"))
+        })
+    }
+
+    /// For score fields without a score
+    pub fn hidden(&self) -> String {
+        format!("{}00", &self.foreground[..self.foreground.len().min(6)])
+    }
+
+    pub fn accent(&self, division: &Division) -> Option<&str> {
+        self.divisions
+            .get(&division.short_name)
+            .or_else(|| self.divisions.get(&division.name))
+            .map(String::as_str)
+    }
+
+    /// The accent of the division, or the foreground if the theme has none for it
+    pub fn accent_or_foreground(&self, division: &Division) -> &str {
+        self.accent(division).unwrap_or(&self.foreground)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colours_have_to_be_hex() {
+        let mut theme = dto::Theme::default();
+        assert!(theme.validate().is_ok());
+        theme.foreground = "#1B2A41".to_string();
+        assert!(theme.validate().is_err());
+        theme.foreground = "1B2A41".to_string();
+        theme.divisions.insert("MPO".to_string(), "red".to_string());
+        assert!(theme.validate().is_err());
+    }
+
+    #[test]
+    fn clones_share_the_active_theme() {
+        let themes = Themes::default();
+        let leaderboard_copy = themes.clone();
+        assert!(themes.set_active("missing").is_err());
+        themes.0.write().unwrap().themes.insert(
+            "sponsor".to_string(),
+            Arc::new(dto::Theme {
+                foreground: "000000".to_string(),
+                ..Default::default()
+            }),
+        );
+        themes.set_active("sponsor").unwrap();
+        assert_eq!(leaderboard_copy.active().hidden(), "00000000");
+    }
+}
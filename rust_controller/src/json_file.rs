@@ -0,0 +1,67 @@
+//! Settings and history kept in JSON files in the working directory.
+//!
+//! Each file is loaded when what it belongs to is made, and saved again every time that changes.
+//! A missing or unreadable file gives the default, so a broken file never stops the controller.
+//! Saves are written on a blocking thread, so they never wait on the disk while the coordinator
+//! is locked, and a save never overwrites a newer one of the same file.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+static SAVES: OnceLock<Mutex<HashMap<&'static str, Arc<SaveOrder>>>> = OnceLock::new();
+
+/// How many saves of a file were made, and which of them was last written
+#[derive(Debug, Default)]
+struct SaveOrder {
+    saves: AtomicUsize,
+    written: Mutex<usize>,
+}
+
+/// The parsed file, or the default if there is none or it can not be parsed
+pub fn load_json_file<T: DeserializeOwned + Default>(path: impl AsRef<Path>) -> T {
+    let path = path.as_ref();
+    let Ok(file) = std::fs::read_to_string(path) else {
+        return T::default();
+    };
+    match serde_json::from_str(&file) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Unable to read {}: {e}", path.display());
+            T::default()
+        }
+    }
+}
+
+/// Writes the value to the file in the background
+pub fn save_json_file<T: Serialize>(path: &'static str, value: &T) {
+    let json = match serde_json::to_string_pretty(value) {
+        Ok(json) => json,
+        Err(e) => {
+            warn!("Unable to save {path}: {e}");
+            return;
+        }
+    };
+    let order = SAVES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(path)
+        .or_default()
+        .clone();
+    let save = order.saves.fetch_add(1, Ordering::SeqCst) + 1;
+    tokio::task::spawn_blocking(move || {
+        let mut written = order.written.lock().unwrap();
+        if *written > save {
+            return;
+        }
+        if let Err(e) = std::fs::write(path, json) {
+            warn!("Unable to save {path}: {e}");
+        }
+        *written = save;
+    });
+}
@@ -4,10 +4,11 @@ extern crate rocket;
 use rocket::{Build, Rocket};
 
 mod api;
+mod assets;
 pub mod controller;
 mod dto;
 pub mod flipup_vmix_controls;
-mod util;
+mod json_file;
 pub mod vmix;
 
 #[launch]
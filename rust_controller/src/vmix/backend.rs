@@ -0,0 +1,65 @@
+//! What graphics are sent to. Every target of the [`crate::vmix::VMixQueue`] is a backend, like a vMix
+//! or a CasparCG server, so the same graphics logic drives either.
+
+use std::fmt::Debug;
+
+use crate::dto;
+use crate::vmix::data_source;
+use crate::vmix::functions::{VMixFunction, VMixInterfacer, VMixSelectionTrait};
+
+pub trait GraphicsBackend: Debug + Send + Sync {
+    fn queue(&self, command: Command);
+
+    fn status(&self) -> dto::VMixConnection;
+
+    /// Sends everything again, for when the output shows something else than it should
+    fn resync(&self);
+}
+
+/// A function for a graphic, as a vMix function and as the parts other backends need
+#[derive(Debug, Clone)]
+pub struct Command {
+    cmd: String,
+    state_key: Option<String>,
+    /// Graphic name like in the title mapping
+    pub graphic: &'static str,
+    /// Input the graphic uses by default, before the title mapping
+    pub input: Option<String>,
+    /// The field as a data key, like in the data sources
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub function: VMixFunction,
+}
+
+impl Command {
+    pub fn new<T: VMixSelectionTrait>(func: &VMixInterfacer<T>) -> Self {
+        let input = func.input.as_ref();
+        let extension = |input: &T| match func.function {
+            VMixFunction::SetTextVisibleOn | VMixFunction::SetTextVisibleOff => "Visible",
+            _ => input.data_extension(),
+        };
+        Self {
+            cmd: func.to_cmd(),
+            state_key: func.state_key(),
+            graphic: T::GRAPHIC_NAME,
+            input: input.map(|input| input.input_name()),
+            key: input
+                .map(|input| data_source::field_key(&input.get_selection_name(), extension(input))),
+            value: func
+                .value
+                .clone()
+                .or_else(|| input.and_then(|input| input.value())),
+            function: func.function.clone(),
+        }
+    }
+
+    /// The vMix function
+    pub fn cmd(&self) -> &str {
+        &self.cmd
+    }
+
+    /// The field this command sets, if the last command to it decides what is shown
+    pub fn state_key(&self) -> Option<&String> {
+        self.state_key.as_ref()
+    }
+}
@@ -0,0 +1,514 @@
+//! CasparCG backend, sending graphics as HTML or Flash templates over AMCP.
+//!
+//! Fields become keys in the template data, named like the data source columns. Graphics are
+//! put on a channel and layer by [`CASPARCG_TEMPLATES_FILE`], looked up by graphic name or by input:
+//!
+//! ```json
+//! { "VMixPlayerInfo": { "channel": 1, "layer": 20, "template": "flipup/scorecard" } }
+//! ```
+//!
+//! Overlay functions play and stop the template of their graphic or input. Like in vMix, an overlay channel
+//! plays one template at a time, and Out or Off without an input stops it. Graphics that are not in the file
+//! are not sent.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::api::Error;
+use crate::dto;
+use crate::dto::VMixConnectionState;
+use crate::json_file::load_json_file;
+use crate::vmix::backend::{Command, GraphicsBackend};
+use crate::vmix::functions::{OverlayAction, VMixFunction};
+use crate::vmix::stream_handler::{
+    parse_address, SendError, MAX_BACKOFF, MIN_BACKOFF, RESPONSE_TIMEOUT,
+};
+
+pub const CASPARCG_TEMPLATES_FILE: &str = "casparcg_templates.json";
+const AMCP_PORT: u16 = 5250;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Template {
+    channel: u16,
+    layer: u16,
+    template: String,
+}
+
+enum Message {
+    Command(Command),
+    Resync,
+}
+
+/// What a layer should show, and what CasparCG was last told
+#[derive(Debug)]
+struct Layer {
+    template: Template,
+    data: BTreeMap<String, String>,
+    playing: bool,
+    /// None if the template is not added on the server
+    sent_data: Option<BTreeMap<String, String>>,
+    sent_playing: bool,
+}
+
+impl Layer {
+    fn target(&self) -> String {
+        format!("{}-{}", self.template.channel, self.template.layer)
+    }
+
+    /// AMCP commands that make the server show what the layer should
+    fn sync(&mut self) -> Vec<String> {
+        let target = self.target();
+        let mut lines = vec![];
+        match &self.sent_data {
+            None if self.data.is_empty() && !self.playing => (),
+            None => {
+                lines.push(format!(
+                    "CG {target} ADD 1 \"{}\" {} \"{}\"",
+                    self.template.template,
+                    u8::from(self.playing),
+                    escape(&self.data)
+                ));
+                self.sent_playing = self.playing;
+            }
+            Some(sent) if *sent != self.data => {
+                lines.push(format!("CG {target} UPDATE 1 \"{}\"", escape(&self.data)))
+            }
+            Some(_) => (),
+        }
+        if !lines.is_empty() {
+            self.sent_data = Some(self.data.clone());
+        }
+        if self.sent_data.is_some() && self.playing != self.sent_playing {
+            let action = if self.playing { "PLAY" } else { "STOP" };
+            lines.push(format!("CG {target} {action} 1"));
+            self.sent_playing = self.playing;
+        }
+        lines
+    }
+}
+
+/// Template data as a quoted AMCP parameter
+fn escape(data: &BTreeMap<String, String>) -> String {
+    serde_json::to_string(data)
+        .unwrap()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+}
+
+#[derive(Debug)]
+struct CasparState {
+    name: String,
+    ip: String,
+    state: VMixConnectionState,
+    connected_since: Option<i64>,
+    reconnects: usize,
+    last_error: Option<String>,
+    templates: HashMap<String, Template>,
+    layers: BTreeMap<String, Layer>,
+    /// The layer each overlay channel plays
+    overlays: HashMap<u8, String>,
+    /// Graphics without a template, only warned about once
+    unmapped: HashSet<String>,
+}
+
+impl CasparState {
+    fn layer(&mut self, names: &[&str]) -> Option<&mut Layer> {
+        let key = self.layer_key(names)?;
+        self.layers.get_mut(&key)
+    }
+
+    /// The layer of the first name with a template, added if it is new
+    fn layer_key(&mut self, names: &[&str]) -> Option<String> {
+        let Some(template) = names.iter().find_map(|name| self.templates.get(*name)) else {
+            if self.unmapped.insert(names.join("/")) {
+                warn!(
+                    "No CasparCG template for {}, add it to {CASPARCG_TEMPLATES_FILE}",
+                    names.join(" or ")
+                );
+            }
+            return None;
+        };
+        let key = format!("{}-{}", template.channel, template.layer);
+        self.layers.entry(key.clone()).or_insert_with(|| Layer {
+            template: template.clone(),
+            data: BTreeMap::new(),
+            playing: false,
+            sent_data: None,
+            sent_playing: false,
+        });
+        Some(key)
+    }
+
+    /// Plays or stops the layer of `names` on the overlay channel, or the one the channel plays if none
+    fn overlay(&mut self, number: u8, action: OverlayAction, names: Option<&[&str]>) {
+        let current = self.overlays.get(&number).cloned();
+        let Some(key) = names.map_or(current.clone(), |names| self.layer_key(names)) else {
+            return;
+        };
+        let play = match action {
+            OverlayAction::In => true,
+            OverlayAction::Toggle => !self.layers[&key].playing,
+            OverlayAction::Out | OverlayAction::Off => false,
+        };
+        if play {
+            if let Some(previous) = current.filter(|previous| *previous != key) {
+                self.set_playing(&previous, false);
+            }
+            self.overlays.insert(number, key.clone());
+        } else if current.as_ref() == Some(&key) {
+            self.overlays.remove(&number);
+        }
+        self.set_playing(&key, play);
+    }
+
+    fn set_playing(&mut self, key: &str, playing: bool) {
+        if let Some(layer) = self.layers.get_mut(key) {
+            layer.playing = playing;
+        }
+    }
+
+    fn apply(&mut self, command: Command) {
+        let input = command.input.clone().unwrap_or_default();
+        match command.function {
+            VMixFunction::SetText | VMixFunction::SetColor | VMixFunction::SetImage => {
+                if let (Some(key), Some(layer)) =
+                    (command.key, self.layer(&[command.graphic, input.as_str()]))
+                {
+                    layer.data.insert(key, command.value.unwrap_or_default());
+                }
+            }
+            VMixFunction::SetTextVisibleOn | VMixFunction::SetTextVisibleOff => {
+                let visible = matches!(command.function, VMixFunction::SetTextVisibleOn);
+                if let (Some(key), Some(layer)) =
+                    (command.key, self.layer(&[command.graphic, input.as_str()]))
+                {
+                    layer.data.insert(key, visible.to_string());
+                }
+            }
+            VMixFunction::OverlayInput { number, action } => {
+                let names = [command.graphic, input.as_str()];
+                self.overlay(
+                    number,
+                    action,
+                    command.input.is_some().then_some(&names[..]),
+                );
+            }
+            // Toggles overlay 4 like in vMix, the input is the value
+            VMixFunction::OverlayInput4 => {
+                let input = command.value.unwrap_or_default();
+                self.overlay(
+                    4,
+                    OverlayAction::Toggle,
+                    Some(&[command.graphic, input.as_str()]),
+                );
+            }
+            VMixFunction::OverlayInput4Off => self.overlay(4, OverlayAction::Off, None),
+            _ => debug!("{} is not supported by CasparCG", command.cmd().trim()),
+        }
+    }
+
+    fn sync(&mut self) -> Vec<String> {
+        self.layers.values_mut().flat_map(Layer::sync).collect()
+    }
+
+    /// Makes every layer be sent again on the next sync
+    fn forget_sent(&mut self) {
+        for layer in self.layers.values_mut() {
+            layer.sent_data = None;
+            layer.sent_playing = false;
+        }
+    }
+
+    fn to_dto(&self) -> dto::VMixConnection {
+        dto::VMixConnection {
+            name: self.name.clone(),
+            ip: self.ip.clone(),
+            state: self.state,
+            connected_since: self.connected_since,
+            reconnects: self.reconnects,
+            last_error: self.last_error.clone(),
+            remembered_fields: self.layers.values().map(|layer| layer.data.len()).sum(),
+        }
+    }
+}
+
+/// Connection to one CasparCG server
+#[derive(Clone, Debug)]
+pub struct CasparClient {
+    sender: UnboundedSender<Message>,
+    state: Arc<Mutex<CasparState>>,
+}
+
+impl CasparClient {
+    /// Connects in the background, and keeps reconnecting for as long as the queue exists.
+    ///
+    /// `ip` can include a port, otherwise the AMCP port is used.
+    pub fn new(name: String, ip: String) -> Result<Self, Error> {
+        let address = parse_address(&ip, AMCP_PORT)?;
+        let (tx, rx) = unbounded_channel();
+        let state = Arc::new(Mutex::new(CasparState {
+            name,
+            ip,
+            state: VMixConnectionState::Connecting,
+            connected_since: None,
+            reconnects: 0,
+            last_error: None,
+            templates: load_json_file(CASPARCG_TEMPLATES_FILE),
+            layers: BTreeMap::new(),
+            overlays: HashMap::new(),
+            unmapped: HashSet::new(),
+        }));
+
+        tokio::spawn(Self::run(address, rx, state.clone()));
+        Ok(Self { sender: tx, state })
+    }
+
+    async fn run(
+        address: SocketAddr,
+        mut rx: UnboundedReceiver<Message>,
+        state: Arc<Mutex<CasparState>>,
+    ) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let error = match TcpStream::connect(address).await {
+                Ok(stream) => {
+                    backoff = MIN_BACKOFF;
+                    {
+                        let mut state = state.lock().unwrap();
+                        if state.connected_since.is_some() {
+                            state.reconnects += 1;
+                        }
+                        state.state = VMixConnectionState::Connected;
+                        state.connected_since = Some(chrono::Utc::now().timestamp());
+                        state.forget_sent();
+                    }
+                    info!("Connected to CasparCG at {address}");
+                    match Self::serve(stream, &mut rx, &state).await {
+                        Some(error) => error,
+                        None => return,
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+            warn!("CasparCG connection to {address} lost: {error}");
+            {
+                let mut state = state.lock().unwrap();
+                state.state = VMixConnectionState::Disconnected;
+                state.last_error = Some(error);
+            }
+
+            // Keep track of what the layers should show while waiting, it is sent on reconnect
+            let sleep = tokio::time::sleep(backoff);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    message = rx.recv() => match message {
+                        Some(Message::Command(command)) => state.lock().unwrap().apply(command),
+                        Some(Message::Resync) => (),
+                        None => return,
+                    },
+                }
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            state.lock().unwrap().state = VMixConnectionState::Connecting;
+        }
+    }
+
+    /// Returns none when the queue is dropped
+    async fn serve(
+        stream: TcpStream,
+        rx: &mut UnboundedReceiver<Message>,
+        state: &Mutex<CasparState>,
+    ) -> Option<String> {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        loop {
+            let lines = state.lock().unwrap().sync();
+            for line in lines {
+                match Self::send(&line, &mut reader, &mut writer).await {
+                    Ok(()) => (),
+                    Err(SendError::Rejected(e)) => warn!("CasparCG rejected {line}: {e}"),
+                    Err(SendError::Disconnected(e)) => return Some(e),
+                }
+            }
+
+            let mut messages = vec![rx.recv().await?];
+            while let Ok(message) = rx.try_recv() {
+                messages.push(message);
+            }
+            let mut guard = state.lock().unwrap();
+            for message in messages {
+                match message {
+                    Message::Command(command) => guard.apply(command),
+                    Message::Resync => guard.forget_sent(),
+                }
+            }
+        }
+    }
+
+    async fn send(
+        line: &str,
+        reader: &mut BufReader<OwnedReadHalf>,
+        writer: &mut OwnedWriteHalf,
+    ) -> Result<(), SendError> {
+        writer
+            .write_all(format!("{line}\r\n").as_bytes())
+            .await
+            .map_err(|e| SendError::Disconnected(e.to_string()))?;
+
+        tokio::time::timeout(RESPONSE_TIMEOUT, Self::read_response(reader))
+            .await
+            .map_err(|_| SendError::Disconnected("CasparCG did not answer in time".to_string()))?
+    }
+
+    /// Reads the answer to a command, with the data lines some answers have
+    async fn read_response(reader: &mut BufReader<OwnedReadHalf>) -> Result<(), SendError> {
+        let line = Self::read_line(reader).await?;
+        match line.get(..3).and_then(|code| code.parse::<u16>().ok()) {
+            Some(200) => {
+                while !Self::read_line(reader).await?.trim().is_empty() {}
+                Ok(())
+            }
+            Some(201) => Self::read_line(reader).await.map(|_| ()),
+            Some(202) => Ok(()),
+            Some(400..=599) => Err(SendError::Rejected(line.trim().to_string())),
+            _ => Err(SendError::Rejected(format!(
+                "Unexpected answer: {}",
+                line.trim()
+            ))),
+        }
+    }
+
+    async fn read_line(reader: &mut BufReader<OwnedReadHalf>) -> Result<String, SendError> {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| SendError::Disconnected(e.to_string()))?;
+        if read == 0 {
+            return Err(SendError::Disconnected(
+                "CasparCG closed the connection".to_string(),
+            ));
+        }
+        Ok(line)
+    }
+}
+
+impl GraphicsBackend for CasparClient {
+    fn queue(&self, command: Command) {
+        if self.sender.send(Message::Command(command)).is_err() {
+            warn!("Failed to send command to queue");
+        }
+    }
+
+    fn status(&self) -> dto::VMixConnection {
+        self.state.lock().unwrap().to_dto()
+    }
+
+    /// Adds every template again with its data
+    fn resync(&self) {
+        if self.sender.send(Message::Resync).is_err() {
+            warn!("Failed to send resync to queue");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vmix::functions::{VMixInterfacer, VMixPlayerInfo};
+
+    fn template() -> Template {
+        Template {
+            channel: 1,
+            layer: 20,
+            template: "flipup/scorecard".to_string(),
+        }
+    }
+
+    fn state() -> CasparState {
+        CasparState {
+            name: "CasparCG".to_string(),
+            ip: "127.0.0.1".to_string(),
+            state: VMixConnectionState::Connected,
+            connected_since: None,
+            reconnects: 0,
+            last_error: None,
+            templates: HashMap::from([("VMixPlayerInfo".to_string(), template())]),
+            layers: BTreeMap::new(),
+            overlays: HashMap::new(),
+            unmapped: HashSet::new(),
+        }
+    }
+
+    fn set_name(name: &str) -> Command {
+        Command::new(&VMixInterfacer::set_text(
+            name.to_string(),
+            VMixPlayerInfo::Name(0),
+        ))
+    }
+
+    fn overlay(action: OverlayAction, input: Option<VMixPlayerInfo>) -> Command {
+        Command::new(&VMixInterfacer::overlay(1, action, input))
+    }
+
+    #[test]
+    fn layer_is_added_then_updated_then_played() {
+        let mut layer = Layer {
+            template: template(),
+            data: BTreeMap::new(),
+            playing: false,
+            sent_data: None,
+            sent_playing: false,
+        };
+        assert!(layer.sync().is_empty());
+        layer.data.insert("p1name".to_string(), "Anna".to_string());
+        assert_eq!(
+            layer.sync(),
+            [r#"CG 1-20 ADD 1 "flipup/scorecard" 0 "{\"p1name\":\"Anna\"}""#]
+        );
+        layer.data.insert("p1name".to_string(), "Bea".to_string());
+        assert_eq!(layer.sync(), [r#"CG 1-20 UPDATE 1 "{\"p1name\":\"Bea\"}""#]);
+        assert!(layer.sync().is_empty());
+        layer.playing = true;
+        assert_eq!(layer.sync(), ["CG 1-20 PLAY 1"]);
+        layer.playing = false;
+        assert_eq!(layer.sync(), ["CG 1-20 STOP 1"]);
+    }
+
+    #[test]
+    fn out_without_input_stops_what_the_channel_plays() {
+        let mut state = state();
+        state.apply(set_name("Anna"));
+        state.apply(overlay(OverlayAction::In, Some(VMixPlayerInfo::Name(0))));
+        assert_eq!(
+            state.sync(),
+            [r#"CG 1-20 ADD 1 "flipup/scorecard" 1 "{\"p1name\":\"Anna\"}""#]
+        );
+        state.apply(overlay(OverlayAction::Out, None));
+        assert_eq!(state.sync(), ["CG 1-20 STOP 1"]);
+    }
+
+    #[test]
+    fn legacy_overlay_4_toggles_and_turns_off() {
+        let mut state = state();
+        state.apply(set_name("Anna"));
+        state.sync();
+        state.apply(Command::new(
+            &VMixInterfacer::<VMixPlayerInfo>::overlay_input_4("Scorecard"),
+        ));
+        assert_eq!(state.sync(), ["CG 1-20 PLAY 1"]);
+        state.apply(Command::new(
+            &VMixInterfacer::<VMixPlayerInfo>::overlay_input_4_off(),
+        ));
+        assert_eq!(state.sync(), ["CG 1-20 STOP 1"]);
+    }
+}
@@ -0,0 +1,131 @@
+//! Graphics as vMix data sources, for titles that poll the coordinator instead of being sent every field.
+//!
+//! A feed is a single row with a column for every field the graphic would set. Columns are named
+//! after the field with anything but letters and numbers replaced by `_`, and without `.Text`,
+//! e.g. `p1name` or `p1h1_Fill_Color`.
+
+use std::collections::BTreeMap;
+
+use crate::vmix::functions::{VMixFunction, VMixInterfacer, VMixSelectionTrait};
+
+/// Column of a field, also used as the data key by other graphics backends.
+/// Valid as a JSON key and as an XML element name
+pub fn field_key(name: &str, extension: &str) -> String {
+    let field = match extension {
+        "Text" | "" => name.to_string(),
+        extension => format!("{name}.{extension}"),
+    };
+    let key: String = field
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{key}")
+    } else {
+        key
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DataSourceRow(BTreeMap<String, String>);
+
+impl DataSourceRow {
+    pub fn new<T: VMixSelectionTrait>(funcs: &[VMixInterfacer<T>]) -> Self {
+        let mut row = Self::default();
+        for func in funcs {
+            let Some(input) = &func.input else {
+                continue;
+            };
+            let value = match func.function {
+                VMixFunction::SetText | VMixFunction::SetColor | VMixFunction::SetImage => {
+                    func.value.clone().or_else(|| input.value())
+                }
+                VMixFunction::SetTextVisibleOn => Some("True".to_string()),
+                VMixFunction::SetTextVisibleOff => Some("False".to_string()),
+                _ => None,
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            let extension = match func.function {
+                VMixFunction::SetTextVisibleOn | VMixFunction::SetTextVisibleOff => "Visible",
+                _ => input.data_extension(),
+            };
+            row.0
+                .insert(field_key(&input.get_selection_name(), extension), value);
+        }
+        row
+    }
+
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.0.get(column).map(String::as_str)
+    }
+
+    /// An array with the row, like vMix expects
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&[&self.0]).unwrap()
+    }
+
+    /// Rows are `/data/row`
+    pub fn to_xml(&self) -> String {
+        let columns: String = self
+            .0
+            .iter()
+            .map(|(column, value)| format!("<{column}>{}</{column}>", Self::escape(value)))
+            .collect();
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><data><row>{columns}</row></data>")
+    }
+
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vmix::functions::{OverlayAction, VMixPlayerInfo};
+
+    #[test]
+    fn fields_become_columns() {
+        let row = DataSourceRow::new(&[
+            VMixInterfacer::set_text("Anna".to_string(), VMixPlayerInfo::Name(0)),
+            VMixInterfacer::set_text_visible_off(VMixPlayerInfo::HotRound(1)),
+            VMixInterfacer::overlay(1, OverlayAction::In, Some(VMixPlayerInfo::Name(2))),
+        ]);
+        assert_eq!(row.get("p1name"), Some("Anna"));
+        assert_eq!(row.get("p2hotrnd_Visible"), Some("False"));
+        assert_eq!(row.get("p3name"), None);
+    }
+
+    #[test]
+    fn last_value_of_a_field_wins() {
+        let row = DataSourceRow::new(&[
+            VMixInterfacer::set_text("Anna".to_string(), VMixPlayerInfo::Name(0)),
+            VMixInterfacer::set_text("Bea".to_string(), VMixPlayerInfo::Name(0)),
+        ]);
+        assert_eq!(row.to_json(), r#"[{"p1name":"Bea"}]"#);
+    }
+
+    #[test]
+    fn xml_values_are_escaped() {
+        let row = DataSourceRow::new(&[VMixInterfacer::set_text(
+            "A&B <C>".to_string(),
+            VMixPlayerInfo::Name(0),
+        )]);
+        assert!(row
+            .to_xml()
+            .ends_with("<data><row><p1name>A&amp;B &lt;C&gt;</p1name></row></data>"));
+    }
+
+    #[test]
+    fn keys_are_valid_names() {
+        assert_eq!(field_key("p1h1", "Fill.Color"), "p1h1_Fill_Color");
+        assert_eq!(field_key("1st", "Text"), "_1st");
+    }
+}
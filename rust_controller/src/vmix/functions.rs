@@ -1,32 +1,50 @@
 use itertools::Itertools;
+use rocket_okapi::okapi::{schemars, schemars::JsonSchema};
+use serde::Deserialize;
 
 use crate::controller::hole::{DroneHoleInfo, FeaturedHole, VMixHoleInfo};
+use crate::controller::stats::{PlayerStat, StatScope};
 use crate::flipup_vmix_controls::{LeaderBoardProperty, LeaderboardTop6};
+use crate::vmix::title_mapping;
 
 pub trait VMixSelectionTrait {
+    /// Input and field name go through the title mapping
     fn get_selection(&self) -> String {
-        let name = self.get_selection_name();
+        let mapping = title_mapping::get();
+        let graphic = self.graphic_name();
+        let input = mapping.input(graphic, self.input_id());
+        let name = mapping.field(graphic, self.input_id(), self.get_selection_name());
         let extension = self.data_extension();
 
         if let Some(value) = self
             .value()
             .and_then(|val| if val.is_empty() { None } else { Some(val) })
         {
-            format!(
-                "Input={}&SelectedName={name}.{extension}&Value={value}",
-                self.input_id()
-            )
+            format!("Input={input}&SelectedName={name}.{extension}&Value={value}")
         } else {
-            format!("Input={}&SelectedName={name}.{extension}", self.input_id())
+            format!("Input={input}&SelectedName={name}.{extension}")
         }
     }
     fn get_selection_name(&self) -> String;
 
+    /// Name of the graphic in the title mapping and in vMix target routing. Users write it in
+    /// their config files, so it stays the same when the type is renamed
+    const GRAPHIC_NAME: &'static str;
+
+    fn graphic_name(&self) -> &'static str {
+        Self::GRAPHIC_NAME
+    }
+
     fn data_extension(&self) -> &'static str;
 
     fn value(&self) -> Option<String>;
 
     fn input_id(&self) -> &'static str;
+
+    /// Input as named in the code, before the title mapping
+    fn input_name(&self) -> String {
+        self.input_id().to_string()
+    }
 }
 
 #[derive(Clone)]
@@ -142,6 +160,84 @@ impl<InputEnum: VMixSelectionTrait> VMixInterfacer<InputEnum> {
             function: VMixFunction::OverlayInput4,
         }
     }
+
+    /// `number` is the overlay channel, 1 to 4. Out and Off do not need an input
+    pub fn overlay(number: u8, action: OverlayAction, input: Option<InputEnum>) -> Self {
+        Self {
+            value: None,
+            input,
+            function: VMixFunction::OverlayInput { number, action },
+        }
+    }
+
+    /// `duration` in milliseconds, vMix uses its default if none. Cut ignores it
+    pub fn transition(kind: Transition, duration: Option<u32>, input: InputEnum) -> Self {
+        Self {
+            value: None,
+            input: Some(input),
+            function: VMixFunction::Transition { kind, duration },
+        }
+    }
+
+    pub fn media(action: MediaAction, input: InputEnum) -> Self {
+        Self {
+            value: None,
+            input: Some(input),
+            function: VMixFunction::Media(action),
+        }
+    }
+
+    /// `index` starts at 1, like in vMix
+    pub fn select_index(index: usize, input: InputEnum) -> Self {
+        Self {
+            value: Some(index.to_string()),
+            input: Some(input),
+            function: VMixFunction::SelectIndex,
+        }
+    }
+
+    /// `time` is formatted like `00:10:00`
+    pub fn set_countdown(time: String, input: InputEnum) -> Self {
+        Self {
+            value: Some(time),
+            input: Some(input),
+            function: VMixFunction::SetCountdown,
+        }
+    }
+
+    /// Marks the last `seconds` of the live recording as a replay event
+    pub fn replay_mark_in_out(seconds: u32) -> Self {
+        Self {
+            value: Some(seconds.to_string()),
+            input: None,
+            function: VMixFunction::Replay(ReplayAction::MarkInOut),
+        }
+    }
+
+    pub fn replay_mark_in() -> Self {
+        Self {
+            value: None,
+            input: None,
+            function: VMixFunction::Replay(ReplayAction::MarkIn),
+        }
+    }
+
+    pub fn replay_mark_out() -> Self {
+        Self {
+            value: None,
+            input: None,
+            function: VMixFunction::Replay(ReplayAction::MarkOut),
+        }
+    }
+
+    /// Names the newest replay event, so it can be found in the replay list
+    pub fn replay_event_text(text: String) -> Self {
+        Self {
+            value: Some(text),
+            input: None,
+            function: VMixFunction::Replay(ReplayAction::SetLastEventText),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -153,12 +249,62 @@ pub enum VMixFunction {
     SetImage,
     OverlayInput4Off,
     OverlayInput4,
+    OverlayInput {
+        number: u8,
+        action: OverlayAction,
+    },
+    Transition {
+        kind: Transition,
+        duration: Option<u32>,
+    },
+    Media(MediaAction),
+    SelectIndex,
+    SetCountdown,
+    Replay(ReplayAction),
+}
+
+#[derive(Debug, Deserialize, JsonSchema, FromFormField, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayAction {
+    In,
+    Out,
+    Toggle,
+    /// Takes the overlay off at once, without its transition
+    Off,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, FromFormField, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Cut,
+    Fade,
+    Merge,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, FromFormField, Clone, Copy, PartialEq, Eq)]
+pub enum MediaAction {
+    Play,
+    Pause,
+    Restart,
+}
+
+/// vMix Replay functions, marking is done on the live recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayAction {
+    MarkIn,
+    MarkOut,
+    MarkInOut,
+    SetLastEventText,
 }
 
 impl<InputEnum: VMixSelectionTrait> VMixInterfacer<InputEnum> {
     fn get_input(&self) -> Option<String> {
         match self.function {
-            VMixFunction::OverlayInput4 => Some(format!("Input={}", self.value.as_ref()?)),
+            VMixFunction::OverlayInput4 => {
+                let input = self.value.as_ref()?;
+                Some(format!(
+                    "Input={}",
+                    title_mapping::get().input(input, input)
+                ))
+            }
             VMixFunction::OverlayInput4Off => None,
             _ => self.input.as_ref().map(|i| i.get_selection().to_owned()),
         }
@@ -170,37 +316,170 @@ impl<InputEnum: VMixSelectionTrait> VMixInterfacer<InputEnum> {
 
     pub fn to_cmd(&self) -> String {
         let cmd = self.function.get_start_cmd();
-        let input = self.get_input();
-        let value = self.get_value();
-
-        "FUNCTION ".to_string()
-            + &match (input, value) {
-                (Some(input), Some(value)) => format!("{cmd} {input}&Value={value}",),
-                (Some(input), None) => format!("{cmd} {input}"),
-                (None, Some(value)) => format!("{cmd} Value={value}"),
-                (None, None) => cmd.to_string(),
-            }
-            + "\r\n"
+        let mut params = vec![];
+        if let Some(input) = self.get_input() {
+            params.push(input);
+        }
+        if let Some(value) = self.get_value() {
+            params.push(format!("Value={value}"));
+        }
+        if let VMixFunction::Transition {
+            duration: Some(duration),
+            ..
+        } = self.function
+        {
+            params.push(format!("Duration={duration}"));
+        }
+
+        if params.is_empty() {
+            format!("FUNCTION {cmd}\r\n")
+        } else {
+            format!("FUNCTION {cmd} {}\r\n", params.join("&"))
+        }
     }
-}
-impl VMixFunction {
-    const fn get_start_cmd(&self) -> &'static str {
-        match self {
+
+    /// The field this function sets, if the last call to it decides what vMix shows.
+    /// Used to send the state again after vMix reconnects
+    pub fn state_key(&self) -> Option<String> {
+        let group = match self.function {
             VMixFunction::SetText => "SetText",
             VMixFunction::SetColor => "SetColor",
-            VMixFunction::SetTextVisibleOn => "SetTextVisibleOn",
-            VMixFunction::SetTextVisibleOff => "SetTextVisibleOff",
+            VMixFunction::SetTextVisibleOn | VMixFunction::SetTextVisibleOff => "SetTextVisible",
             VMixFunction::SetImage => "SetImage",
-            VMixFunction::OverlayInput4Off => "OverlayInput4Off",
-            VMixFunction::OverlayInput4 => "OverlayInput4",
+            VMixFunction::OverlayInput4Off
+            | VMixFunction::OverlayInput4
+            | VMixFunction::OverlayInput { .. }
+            | VMixFunction::Transition { .. }
+            | VMixFunction::Media(_)
+            | VMixFunction::SelectIndex
+            | VMixFunction::SetCountdown
+            | VMixFunction::Replay(_) => None?,
+        };
+        let selection = self.input.as_ref()?.get_selection();
+        let field = selection
+            .split_once("&Value=")
+            .map(|(field, _)| field)
+            .unwrap_or(&selection);
+        Some(format!("{group} {field}"))
+    }
+}
+impl VMixFunction {
+    fn get_start_cmd(&self) -> String {
+        match self {
+            VMixFunction::SetText => "SetText".to_string(),
+            VMixFunction::SetColor => "SetColor".to_string(),
+            VMixFunction::SetTextVisibleOn => "SetTextVisibleOn".to_string(),
+            VMixFunction::SetTextVisibleOff => "SetTextVisibleOff".to_string(),
+            VMixFunction::SetImage => "SetImage".to_string(),
+            VMixFunction::OverlayInput4Off => "OverlayInput4Off".to_string(),
+            VMixFunction::OverlayInput4 => "OverlayInput4".to_string(),
+            VMixFunction::OverlayInput { number, action } => match action {
+                OverlayAction::In => format!("OverlayInput{number}In"),
+                OverlayAction::Out => format!("OverlayInput{number}Out"),
+                OverlayAction::Toggle => format!("OverlayInput{number}"),
+                OverlayAction::Off => format!("OverlayInput{number}Off"),
+            },
+            VMixFunction::Transition { kind, .. } => format!("{kind:?}"),
+            VMixFunction::Media(action) => format!("{action:?}"),
+            VMixFunction::SelectIndex => "SelectIndex".to_string(),
+            VMixFunction::SetCountdown => "SetCountdown".to_string(),
+            VMixFunction::Replay(action) => match action {
+                ReplayAction::MarkIn => "ReplayMarkInLive",
+                ReplayAction::MarkOut => "ReplayMarkOut",
+                ReplayAction::MarkInOut => "ReplayMarkInOutLive",
+                ReplayAction::SetLastEventText => "ReplaySetLastEventText",
+            }
+            .to_string(),
         }
     }
 }
 
+/// Functions for the vMix Replay input. Named `ReplayMark` in vMix target routing
+#[derive(Clone, Debug)]
+pub struct ReplayMark;
+
+impl VMixSelectionTrait for ReplayMark {
+    const GRAPHIC_NAME: &'static str = "ReplayMark";
+
+    fn get_selection(&self) -> String {
+        String::new()
+    }
+
+    fn get_selection_name(&self) -> String {
+        String::new()
+    }
+
+    fn data_extension(&self) -> &'static str {
+        ""
+    }
+
+    fn value(&self) -> Option<String> {
+        None
+    }
+
+    /// Not used, replay functions go to the replay input
+    fn input_id(&self) -> &'static str {
+        ""
+    }
+}
+
+/// An input, or a field of it like `Timer.Text`, named in an API call.
+/// The input can be a GUID, key, number or name, or a title in the title mapping
+#[derive(Clone, Debug)]
+pub struct InputSelection {
+    pub input: String,
+    pub field: Option<String>,
+}
+
+impl InputSelection {
+    pub fn input(input: String) -> Self {
+        Self { input, field: None }
+    }
+}
+
+impl VMixSelectionTrait for InputSelection {
+    const GRAPHIC_NAME: &'static str = "InputSelection";
+
+    fn get_selection(&self) -> String {
+        let input = title_mapping::get().input(&self.input, &self.input);
+        match &self.field {
+            Some(field) => format!("Input={input}&SelectedName={field}"),
+            None => format!("Input={input}"),
+        }
+    }
+
+    fn get_selection_name(&self) -> String {
+        self.field.clone().unwrap_or_default()
+    }
+
+    fn data_extension(&self) -> &'static str {
+        ""
+    }
+
+    fn value(&self) -> Option<String> {
+        None
+    }
+
+    /// Not used, the input is only known at runtime
+    fn input_id(&self) -> &'static str {
+        ""
+    }
+
+    fn input_name(&self) -> String {
+        self.input.clone()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum VMixPlayerInfo {
-    Score { hole: usize, player: usize },
-    ScoreColor { hole: usize, player: usize },
+    Score {
+        hole: usize,
+        player: usize,
+    },
+    ScoreColor {
+        hole: usize,
+        player: usize,
+    },
     Name(usize),
     Surname(usize),
     TotalScore(usize),
@@ -212,9 +491,16 @@ pub enum VMixPlayerInfo {
     HotRound(usize),
     InsidePutt(usize),
     CircleHit(usize),
+    Stat {
+        index: usize,
+        stat: PlayerStat,
+        scope: StatScope,
+    },
 }
 
 impl VMixSelectionTrait for VMixPlayerInfo {
+    const GRAPHIC_NAME: &'static str = "VMixPlayerInfo";
+
     fn get_selection_name(&self) -> String {
         match self {
             VMixPlayerInfo::Score { hole, player } => {
@@ -238,6 +524,12 @@ impl VMixSelectionTrait for VMixPlayerInfo {
             VMixPlayerInfo::HotRound(pos) => format!("p{}hotrnd", pos + 1),
             VMixPlayerInfo::CircleHit(pos) => format!("p{}c1reg", pos + 1),
             VMixPlayerInfo::InsidePutt(pos) => format!("p{}c1x", pos + 1),
+            VMixPlayerInfo::Stat { index, stat, scope } => format!(
+                "p{}{}{}",
+                index + 1,
+                stat.selection_name(),
+                scope.selection_suffix()
+            ),
         }
     }
 
@@ -253,7 +545,8 @@ impl VMixSelectionTrait for VMixPlayerInfo {
             | TotalScore(_)
             | PositionMove(_)
             | InsidePutt(_)
-            | CircleHit(_) => "Text",
+            | CircleHit(_)
+            | Stat { .. } => "Text",
             ScoreColor { .. } => "Fill.Color",
             PositionArrow(_) | HotRound(_) => "Source",
         }
@@ -270,6 +563,8 @@ impl VMixSelectionTrait for VMixPlayerInfo {
 pub struct CurrentPlayer(pub VMixPlayerInfo);
 
 impl VMixSelectionTrait for CurrentPlayer {
+    const GRAPHIC_NAME: &'static str = "CurrentPlayer";
+
     fn get_selection_name(&self) -> String {
         self.0.get_selection_name()
     }
@@ -320,7 +615,8 @@ impl VMixInterfacer<VMixPlayerInfo> {
                 | VMixPlayerInfo::PositionMove(n)
                 | VMixPlayerInfo::HotRound(n)
                 | VMixPlayerInfo::InsidePutt(n)
-                | VMixPlayerInfo::CircleHit(n) => {
+                | VMixPlayerInfo::CircleHit(n)
+                | VMixPlayerInfo::Stat { index: n, .. } => {
                     *n = index;
                 }
             }
@@ -358,6 +654,8 @@ pub enum Compare2x2 {
 }
 
 impl VMixSelectionTrait for Compare2x2 {
+    const GRAPHIC_NAME: &'static str = "Compare2x2";
+
     fn get_selection_name(&self) -> String {
         match self {
             Compare2x2::Standard(s) => s.get_selection_name(),
@@ -395,6 +693,8 @@ impl VMixInterfacer<Compare2x2> {
 }
 
 impl VMixSelectionTrait for Featured {
+    const GRAPHIC_NAME: &'static str = "Featured";
+
     fn get_selection_name(&self) -> String {
         self.0.get_selection_name()
     }
@@ -0,0 +1,116 @@
+//! Every command the coordinator makes for vMix, with what caused it.
+//!
+//! The newest [`MAX_ENTRIES`] are kept in memory, and everything is appended to [`JOURNAL_FILE`],
+//! which is moved to [`OLD_JOURNAL_FILE`] when it grows past [`MAX_FILE_SIZE`].
+//! The file is written on its own thread, so sending to vMix never waits for it.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+use crate::dto;
+
+pub const JOURNAL_FILE: &str = "vmix_journal.jsonl";
+pub const OLD_JOURNAL_FILE: &str = "vmix_journal.1.jsonl";
+const MAX_ENTRIES: usize = 1000;
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+tokio::task_local! {
+    /// What the current task is, like `update loop`. Used for commands that are not queued by an API call
+    pub static CAUSE: String;
+}
+
+#[derive(Debug)]
+pub struct Journal {
+    entries: Mutex<VecDeque<dto::JournalEntry>>,
+    writer: Mutex<Sender<dto::JournalEntry>>,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        let (writer, entries) = channel();
+        std::thread::spawn(move || Self::write_all(entries));
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(MAX_ENTRIES)),
+            writer: Mutex::new(writer),
+        }
+    }
+
+    fn open() -> Option<File> {
+        File::options()
+            .create(true)
+            .append(true)
+            .open(Path::new(JOURNAL_FILE))
+            .map_err(|e| warn!("Unable to open {JOURNAL_FILE}: {e}"))
+            .ok()
+    }
+
+    /// The API call that queued the command, or else the task it was made in
+    fn cause(api_call: Option<&str>) -> String {
+        match api_call {
+            Some(cause) => cause.to_string(),
+            None => CAUSE
+                .try_with(Clone::clone)
+                .unwrap_or_else(|_| "unknown".to_string()),
+        }
+    }
+
+    pub fn record(&self, cause: Option<&str>, graphic: &str, targets: Vec<String>, cmd: &str) {
+        let entry = dto::JournalEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            cause: Self::cause(cause),
+            graphic: graphic.to_string(),
+            targets,
+            command: cmd.trim().to_string(),
+        };
+        // Only fails if the writer thread is gone, and it has already warned about why
+        let _ = self.writer.lock().unwrap().send(entry.clone());
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Appends every entry to the file until the journal is dropped
+    fn write_all(entries: Receiver<dto::JournalEntry>) {
+        let mut file = Self::open();
+        for entry in entries {
+            let Some(journal) = file.as_mut() else {
+                continue;
+            };
+            let line = serde_json::to_string(&entry).unwrap();
+            if let Err(e) = writeln!(journal, "{line}") {
+                warn!("Unable to write to {JOURNAL_FILE}: {e}");
+                continue;
+            }
+            if journal
+                .metadata()
+                .is_ok_and(|metadata| metadata.len() > MAX_FILE_SIZE)
+            {
+                drop(file.take());
+                if let Err(e) = std::fs::rename(JOURNAL_FILE, OLD_JOURNAL_FILE) {
+                    warn!("Unable to rotate {JOURNAL_FILE}: {e}");
+                }
+                file = Self::open();
+            }
+        }
+    }
+
+    /// The newest entries, oldest first
+    pub fn entries(&self, limit: Option<usize>) -> Vec<dto::JournalEntry> {
+        let entries = self.entries.lock().unwrap();
+        let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+        entries.iter().skip(skip).cloned().collect()
+    }
+}
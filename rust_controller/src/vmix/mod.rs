@@ -1,5 +1,11 @@
+mod backend;
+mod casparcg;
 pub mod conversions;
+pub mod data_source;
 pub mod functions;
+pub mod journal;
+mod queue;
 mod stream_handler;
+pub mod title_mapping;
 
-pub use stream_handler::VMixQueue;
+pub use queue::VMixQueue;
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use crate::api::Error;
+use crate::dto;
+use crate::vmix::backend::{Command, GraphicsBackend};
+use crate::vmix::casparcg::CasparClient;
+use crate::vmix::functions::{VMixInterfacer, VMixSelectionTrait};
+use crate::vmix::journal::Journal;
+use crate::vmix::stream_handler::VMixClient;
+use crate::vmix::title_mapping;
+
+#[derive(Clone, Debug)]
+struct Target {
+    name: String,
+    /// None in dry-run mode
+    client: Option<Arc<dyn GraphicsBackend>>,
+    graphics: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Target {
+    fn accepts(&self, graphic: &str) -> bool {
+        (self.graphics.is_empty() || self.graphics.iter().any(|name| name == graphic))
+            && !self.exclude.iter().any(|name| name == graphic)
+    }
+}
+
+/// Sends every function to each vMix target whose routing accepts the graphic, and writes it to the journal.
+///
+/// In dry-run mode nothing is sent, the journal still shows where every function would have gone.
+/// Graphics the title mapping does not push are journaled without targets.
+#[derive(Clone, Debug)]
+pub struct VMixQueue {
+    targets: Vec<Target>,
+    journal: Arc<Journal>,
+    /// The API call queueing the commands, for the journal
+    cause: Option<String>,
+}
+
+impl VMixQueue {
+    pub fn new(targets: Vec<dto::VMixTarget>, dry_run: bool) -> Result<Self, Error> {
+        let targets = targets
+            .into_iter()
+            .map(|target| {
+                Ok(Target {
+                    client: if dry_run {
+                        None
+                    } else {
+                        Some(Self::connect(&target)?)
+                    },
+                    name: target.name,
+                    graphics: target.graphics,
+                    exclude: target.exclude,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            targets,
+            journal: Arc::new(Journal::new()),
+            cause: Some("startup".to_string()),
+        })
+    }
+
+    fn connect(target: &dto::VMixTarget) -> Result<Arc<dyn GraphicsBackend>, Error> {
+        let (name, ip) = (target.name.clone(), target.ip.clone());
+        Ok(match target.backend {
+            dto::GraphicsBackend::VMix => Arc::new(VMixClient::new(name, ip)?),
+            dto::GraphicsBackend::CasparCG => Arc::new(CasparClient::new(name, ip)?),
+        })
+    }
+
+    pub fn add_ref<'a, T: VMixSelectionTrait + 'a>(
+        &self,
+        functions: impl Iterator<Item = &'a VMixInterfacer<T>>,
+    ) {
+        let graphic = T::GRAPHIC_NAME;
+        let targets = self
+            .targets
+            .iter()
+            .filter(|target| target.accepts(graphic))
+            .collect::<Vec<_>>();
+        let names = targets
+            .iter()
+            .map(|target| target.name.clone())
+            .collect::<Vec<_>>();
+        let mapping = title_mapping::get();
+        for func in functions {
+            let command = Command::new(func);
+            let default_input = func.input.as_ref().map_or("", |input| input.input_id());
+            if !mapping.push(graphic, default_input) {
+                self.journal
+                    .record(self.cause.as_deref(), graphic, vec![], command.cmd());
+                continue;
+            }
+            self.journal
+                .record(self.cause.as_deref(), graphic, names.clone(), command.cmd());
+            for client in targets.iter().filter_map(|target| target.client.as_ref()) {
+                client.queue(command.clone());
+            }
+        }
+    }
+
+    pub fn add<T: VMixSelectionTrait>(&self, functions: impl Iterator<Item = VMixInterfacer<T>>) {
+        self.add_ref(functions.collect::<Vec<_>>().iter())
+    }
+
+    pub fn status(&self) -> Vec<dto::VMixConnection> {
+        self.targets
+            .iter()
+            .filter_map(|target| target.client.as_ref().map(|client| client.status()))
+            .collect()
+    }
+
+    pub fn journal(&self, limit: Option<usize>) -> Vec<dto::JournalEntry> {
+        self.journal.entries(limit)
+    }
+
+    /// Puts the following commands on an API call, see [`crate::vmix::journal::CAUSE`] for loops
+    pub fn set_cause(&mut self, cause: Option<String>) {
+        self.cause = cause;
+    }
+
+    /// Resyncs every target if none is given
+    pub fn resync(&self, target: Option<&str>) -> Result<(), Error> {
+        match target {
+            Some(name) => self
+                .targets
+                .iter()
+                .find(|target| target.name == name)
+                .ok_or(Error::VMixTargetNotFound(name.to_string()))?
+                .client
+                .iter()
+                .for_each(|client| client.resync()),
+            None => self
+                .targets
+                .iter()
+                .filter_map(|target| target.client.as_ref())
+                .for_each(|client| client.resync()),
+        }
+        Ok(())
+    }
+}
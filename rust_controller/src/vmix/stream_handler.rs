@@ -1,112 +1,388 @@
-use tokio::sync::broadcast::{channel, Receiver, Sender};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-#[cfg(not(target_arch = "wasm32"))]
-use {
-    std::io::{Read, Write},
-    std::net::{IpAddr, SocketAddr, TcpStream},
-    std::str::FromStr,
-};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
 use crate::api::Error;
-use crate::vmix::functions::{VMixInterfacer, VMixSelectionTrait};
+use crate::dto;
+use crate::dto::VMixConnectionState;
+use crate::vmix::backend::{Command, GraphicsBackend};
 
-#[cfg(target_arch = "wasm32")]
-#[derive(Clone)]
-pub struct Queue {
-    functions: Arc<Mutex<VecDeque<String>>>,
+const VMIX_PORT: u16 = 8099;
+pub(super) const MIN_BACKOFF: Duration = Duration::from_millis(500);
+pub(super) const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How long vMix gets to answer a function before the connection counts as dead
+pub(super) const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Message {
+    Command(Command),
+    /// Send every remembered field again, even if vMix should already show it
+    Resync,
+}
+
+pub(super) enum SendError {
+    /// The connection is gone and has to be made again
+    Disconnected(String),
+    /// vMix answered, but did not run the function
+    Rejected(String),
+}
+
+/// Connection status, and a shadow of what every field in vMix should show.
+///
+/// The shadow holds the last command for every field. Commands that would not change a field are not sent.
+#[derive(Debug)]
+struct ConnectionState {
+    name: String,
     ip: String,
-    client: reqwest::Client,
+    state: VMixConnectionState,
+    connected_since: Option<i64>,
+    reconnects: usize,
+    last_error: Option<String>,
+    fields: HashMap<String, String>,
+}
+
+impl ConnectionState {
+    fn remember(&mut self, command: &Command) {
+        if let Some(key) = command.state_key() {
+            self.fields.insert(key.clone(), command.cmd().to_string());
+        }
+    }
+
+    fn is_unchanged(&self, command: &Command) -> bool {
+        command
+            .state_key()
+            .is_some_and(|key| self.fields.get(key).map(String::as_str) == Some(command.cmd()))
+    }
+
+    fn forget(&mut self, command: &Command) {
+        if let Some(key) = command.state_key() {
+            self.fields.remove(key);
+        }
+    }
+
+    fn to_dto(&self) -> dto::VMixConnection {
+        dto::VMixConnection {
+            name: self.name.clone(),
+            ip: self.ip.clone(),
+            state: self.state,
+            connected_since: self.connected_since,
+            reconnects: self.reconnects,
+            last_error: self.last_error.clone(),
+            remembered_fields: self.fields.len(),
+        }
+    }
+}
+
+/// `ip` with `port` if it does not include one
+pub(super) fn parse_address(ip: &str, port: u16) -> Result<SocketAddr, Error> {
+    SocketAddr::from_str(ip)
+        .or_else(|_| IpAddr::from_str(ip).map(|ip| SocketAddr::new(ip, port)))
+        .map_err(|_| Error::IpNotFound(ip.to_string()))
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+/// Connection to one vMix machine
 #[derive(Clone, Debug)]
-pub struct VMixQueue {
-    functions_sender: tokio::sync::broadcast::Sender<String>,
+pub struct VMixClient {
+    functions_sender: UnboundedSender<Message>,
+    state: Arc<Mutex<ConnectionState>>,
 }
 
-impl VMixQueue {
-    pub fn new(ip: String) -> Result<Self, Error> {
-        let (tx, mut rx): (Sender<String>, Receiver<String>) = channel(2048);
-        let mut stream = Self::make_tcp_stream(&ip).ok_or(Error::IpNotFound(ip))?;
+impl VMixClient {
+    /// Connects in the background, and keeps reconnecting for as long as the queue exists.
+    ///
+    /// `ip` can include a port, like `127.0.0.1:8100` for the mock vMix, otherwise the vMix port is used.
+    pub fn new(name: String, ip: String) -> Result<Self, Error> {
+        let address = parse_address(&ip, VMIX_PORT)?;
+        let (tx, rx) = unbounded_channel();
+        let state = Arc::new(Mutex::new(ConnectionState {
+            name,
+            ip,
+            state: VMixConnectionState::Connecting,
+            connected_since: None,
+            reconnects: 0,
+            last_error: None,
+            fields: HashMap::new(),
+        }));
 
-        let me = Self {
+        tokio::spawn(Self::run(address, rx, state.clone()));
+        Ok(Self {
             functions_sender: tx,
-        };
-        // Here is the actual thread that clears the queue:
-        tokio::spawn(async move {
-            loop {
-                if let Ok(f) = rx.recv().await {
-                    match VMixQueue::send(&f.into_bytes(), &mut stream) {
-                        Ok(()) => (),
-                        Err(e) => {
-                            warn!("{}", e);
+            state,
+        })
+    }
+
+    async fn run(
+        address: SocketAddr,
+        mut rx: UnboundedReceiver<Message>,
+        state: Arc<Mutex<ConnectionState>>,
+    ) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let error = match TcpStream::connect(address).await {
+                Ok(stream) => {
+                    backoff = MIN_BACKOFF;
+                    let replay = {
+                        let mut state = state.lock().unwrap();
+                        if state.connected_since.is_some() {
+                            state.reconnects += 1;
                         }
+                        state.state = VMixConnectionState::Connected;
+                        state.connected_since = Some(chrono::Utc::now().timestamp());
+                        state.fields.values().cloned().collect::<Vec<_>>()
                     };
+                    info!("Connected to vMix at {address}");
+                    match Self::serve(stream, replay, &mut rx, &state).await {
+                        Some(error) => error,
+                        // The queue was dropped
+                        None => return,
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+            warn!("vMix connection to {address} lost: {error}");
+            {
+                let mut state = state.lock().unwrap();
+                state.state = VMixConnectionState::Disconnected;
+                state.last_error = Some(error);
+            }
+
+            // Keep track of what the fields should be while waiting, so it can be sent on reconnect
+            let sleep = tokio::time::sleep(backoff);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    message = rx.recv() => match message {
+                        Some(Message::Command(command)) => state.lock().unwrap().remember(&command),
+                        // Everything is sent on reconnect anyway
+                        Some(Message::Resync) => (),
+                        None => return,
+                    },
                 }
             }
-        });
-        Ok(me)
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            state.lock().unwrap().state = VMixConnectionState::Connecting;
+        }
     }
 
-    fn make_tcp_stream(ip: &str) -> Option<TcpStream> {
-        TcpStream::connect(SocketAddr::new(IpAddr::from_str(ip).unwrap(), 8099))
-            .map_err(|err| {
-                println!("TCP STREAM BUGGED OUT: {err}");
-            })
-            .ok()
+    /// Sends the replayed state and then the queue until the connection breaks.
+    /// Returns none when the queue is dropped
+    async fn serve(
+        stream: TcpStream,
+        replay: Vec<String>,
+        rx: &mut UnboundedReceiver<Message>,
+        state: &Mutex<ConnectionState>,
+    ) -> Option<String> {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        if let Err(e) = Self::send_all(replay, &mut reader, &mut writer).await {
+            return Some(e);
+        }
+        loop {
+            let mut messages = vec![rx.recv().await?];
+            while let Ok(message) = rx.try_recv() {
+                messages.push(message);
+            }
+
+            let mut resync = false;
+            let commands =
+                Self::collapse(messages.into_iter().filter_map(|message| match message {
+                    Message::Command(command) => Some(command),
+                    Message::Resync => {
+                        resync = true;
+                        None
+                    }
+                }));
+            for command in commands {
+                if state.lock().unwrap().is_unchanged(&command) {
+                    continue;
+                }
+                state.lock().unwrap().remember(&command);
+                match Self::send(command.cmd(), &mut reader, &mut writer).await {
+                    Ok(()) => (),
+                    Err(SendError::Rejected(e)) => {
+                        warn!("{e}");
+                        // vMix does not show it, so the same command should not be dropped next time
+                        state.lock().unwrap().forget(&command);
+                    }
+                    Err(SendError::Disconnected(e)) => return Some(e),
+                }
+            }
+            if resync {
+                let fields = state.lock().unwrap().fields.values().cloned().collect();
+                if let Err(e) = Self::send_all(fields, &mut reader, &mut writer).await {
+                    return Some(e);
+                }
+            }
+        }
+    }
+
+    /// Of several commands to the same field, only the last is kept, in the place of the last one.
+    /// Commands without a field, like overlays, are never moved past, so what they show is set first
+    fn collapse(commands: impl Iterator<Item = Command>) -> Vec<Command> {
+        let mut collapsed = vec![];
+        let mut run = vec![];
+        for command in commands {
+            if command.state_key().is_some() {
+                run.push(command);
+            } else {
+                Self::collapse_run(&mut run, &mut collapsed);
+                collapsed.push(command);
+            }
+        }
+        Self::collapse_run(&mut run, &mut collapsed);
+        collapsed
     }
 
-    fn send(bytes: &[u8], stream: &mut TcpStream) -> Result<(), String> {
-        match stream.write_all(bytes) {
-            Ok(()) => (),
-            Err(e) => Err(e.to_string())?,
+    /// Moves a run of commands that all set fields, keeping the last one to every field
+    fn collapse_run(run: &mut Vec<Command>, collapsed: &mut Vec<Command>) {
+        let mut last = HashMap::new();
+        for (index, command) in run.iter().enumerate() {
+            if let Some(key) = command.state_key() {
+                last.insert(key.clone(), index);
+            }
         }
+        collapsed.extend(
+            run.drain(..)
+                .enumerate()
+                .filter(|(index, command)| {
+                    command.state_key().is_some_and(|key| last[key] == *index)
+                })
+                .map(|(_, command)| command),
+        );
+    }
 
-        let mut response = Vec::new();
-        let mut buff = [0; 256];
+    /// Sends every command, only stopping if the connection breaks
+    async fn send_all(
+        cmds: Vec<String>,
+        reader: &mut BufReader<OwnedReadHalf>,
+        writer: &mut OwnedWriteHalf,
+    ) -> Result<(), String> {
+        for cmd in cmds {
+            match Self::send(&cmd, reader, writer).await {
+                Ok(()) => (),
+                Err(SendError::Rejected(e)) => warn!("vMix rejected replayed function: {e}"),
+                Err(SendError::Disconnected(e)) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(
+        cmd: &str,
+        reader: &mut BufReader<OwnedReadHalf>,
+        writer: &mut OwnedWriteHalf,
+    ) -> Result<(), SendError> {
+        writer
+            .write_all(cmd.as_bytes())
+            .await
+            .map_err(|e| SendError::Disconnected(e.to_string()))?;
+
+        tokio::time::timeout(RESPONSE_TIMEOUT, Self::read_response(reader))
+            .await
+            .map_err(|_| SendError::Disconnected("vMix did not answer in time".to_string()))?
+    }
+
+    /// Reads lines until the answer to a function, skipping anything else vMix sends
+    async fn read_response(reader: &mut BufReader<OwnedReadHalf>) -> Result<(), SendError> {
+        let mut line = String::new();
         loop {
-            let number_of_bytes = match stream.read(&mut buff) {
-                Ok(n) => n,
-                Err(e) => Err(dbg!(e).to_string())?,
-            };
-            response.extend_from_slice(&buff[..number_of_bytes]);
-            if response.ends_with(b"\r\n") {
-                break;
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .await
+                .map_err(|e| SendError::Disconnected(e.to_string()))?;
+            if read == 0 {
+                return Err(SendError::Disconnected(
+                    "vMix closed the connection".to_string(),
+                ));
+            }
+            if let Some(response) = Self::parse_response(&line) {
+                return response;
             }
         }
-        Self::parse_buffer(String::from_utf8(response).unwrap())
     }
 
-    fn parse_buffer(buf: String) -> Result<(), String> {
-        if buf.contains("OK") {
-            Ok(())
+    /// None if the line is not an answer to a function
+    fn parse_response(line: &str) -> Option<Result<(), SendError>> {
+        let response = line.trim().strip_prefix("FUNCTION ")?;
+        if response.starts_with("OK") {
+            Some(Ok(()))
+        } else if let Some(error) = response.strip_prefix("ER") {
+            Some(Err(SendError::Rejected(error.trim().to_string())))
         } else {
-            Err(buf.split("ER ").collect::<Vec<_>>()[1].trim().to_string())
+            warn!("Unexpected answer from vMix: {}", line.trim());
+            None
         }
     }
+}
 
-    pub fn add_ref<'a, T: VMixSelectionTrait + 'a>(
-        &self,
-        functions: impl Iterator<Item = &'a VMixInterfacer<T>>,
-    ) {
-        for func in functions {
-            match self.functions_sender.send(func.to_cmd()) {
-                Ok(_) => (),
-                Err(e) => {
-                    warn!("Failed to send command to queue: {e}");
-                }
-            };
+impl GraphicsBackend for VMixClient {
+    fn queue(&self, command: Command) {
+        if self
+            .functions_sender
+            .send(Message::Command(command))
+            .is_err()
+        {
+            warn!("Failed to send command to queue");
         }
     }
 
-    pub fn add<T: VMixSelectionTrait>(&self, functions: impl Iterator<Item = VMixInterfacer<T>>) {
-        for func in functions {
-            match self.functions_sender.send(func.to_cmd()) {
-                Ok(_) => (),
-                Err(e) => {
-                    warn!("Failed to send command to queue: {e}");
-                }
-            };
+    fn status(&self) -> dto::VMixConnection {
+        self.state.lock().unwrap().to_dto()
+    }
+
+    /// Sends every remembered field again
+    fn resync(&self) {
+        if self.functions_sender.send(Message::Resync).is_err() {
+            warn!("Failed to send resync to queue");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vmix::functions::{OverlayAction, VMixInterfacer, VMixPlayerInfo};
+
+    fn set_name(name: &str) -> Command {
+        Command::new(&VMixInterfacer::set_text(
+            name.to_string(),
+            VMixPlayerInfo::Name(0),
+        ))
+    }
+
+    fn overlay_in() -> Command {
+        Command::new(&VMixInterfacer::<VMixPlayerInfo>::overlay(
+            1,
+            OverlayAction::In,
+            None,
+        ))
+    }
+
+    fn cmds(commands: Vec<Command>) -> Vec<String> {
+        commands
+            .iter()
+            .map(|command| command.cmd().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn last_set_to_a_field_wins() {
+        let collapsed = VMixClient::collapse(vec![set_name("a"), set_name("b")].into_iter());
+        assert_eq!(cmds(collapsed), cmds(vec![set_name("b")]));
+    }
+
+    #[test]
+    fn field_is_not_moved_past_an_overlay() {
+        let commands = vec![set_name("a"), overlay_in(), set_name("b")];
+        let collapsed = VMixClient::collapse(commands.clone().into_iter());
+        assert_eq!(cmds(collapsed), cmds(commands));
+    }
+}
@@ -0,0 +1,241 @@
+//! Which vMix input and field names every graphic writes to, read from [`TITLE_MAPPING_FILE`] at startup.
+//!
+//! Titles are looked up by graphic name, e.g. `VMixPlayerInfo` or `CurrentPlayer`, or by the
+//! input GUID they use by default. Inputs can be given as a GUID, key, number or name.
+//! Field templates rename fields, `{name}` placeholders match numbers. Templates are tried in
+//! the order they are written, the first one that matches wins:
+//!
+//! ```json
+//! {
+//!     "VMixPlayerInfo": {
+//!         "input": "Scorecard",
+//!         "fields": { "p{player}s{hole}": "Player{player}Hole{hole}" }
+//!     },
+//!     "50 ob.mov": { "input": "OB animation" },
+//!     "LeaderBoardProperty": { "push": false }
+//! }
+//! ```
+//!
+//! Graphics with `"push": false` are not sent to vMix, for titles that read them as a data source instead.
+//!
+//! Anything not in the file keeps the input and field names set in code. Graphics in
+//! [`REQUIRED_INPUTS`] have no title in the default vMix project, so they need an `input` here.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::api::Error;
+use crate::json_file::load_json_file;
+
+pub const TITLE_MAPPING_FILE: &str = "title_mapping.json";
+
+/// Graphics that can not be shown until the title mapping gives them an input
+pub const REQUIRED_INPUTS: [&str; 2] = ["ClubStandingsProperty", "RoundSummaryProperty"];
+
+static TITLE_MAPPING: OnceLock<TitleMapping> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct TitleMapping(HashMap<String, TitleConfig>);
+
+#[derive(Debug, Deserialize)]
+struct TitleConfig {
+    input: Option<String>,
+    #[serde(default, deserialize_with = "in_file_order")]
+    fields: Vec<(String, String)>,
+    #[serde(default = "push_by_default")]
+    push: bool,
+}
+
+fn push_by_default() -> bool {
+    true
+}
+
+/// A JSON object as its entries, in the order they are written
+fn in_file_order<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(String, String)>, D::Error> {
+    struct EntriesVisitor;
+
+    impl<'de> Visitor<'de> for EntriesVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an object of field names")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or_default());
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(EntriesVisitor)
+}
+
+/// Reads [`TITLE_MAPPING_FILE`]. Only the first call has an effect
+pub fn load() {
+    let mapping = TITLE_MAPPING.get_or_init(|| load_json_file(TITLE_MAPPING_FILE));
+    for graphic in REQUIRED_INPUTS {
+        if let Err(e) = mapping.require_input(graphic) {
+            warn!("{e}");
+        }
+    }
+}
+
+/// The loaded title mapping, empty if [`load`] has not been called
+pub fn get() -> &'static TitleMapping {
+    TITLE_MAPPING.get_or_init(TitleMapping::default)
+}
+
+impl TitleMapping {
+    fn title(&self, graphic: &str, default_input: &str) -> Option<&TitleConfig> {
+        self.0.get(graphic).or_else(|| self.0.get(default_input))
+    }
+
+    pub fn input<'a>(&'a self, graphic: &str, default_input: &'a str) -> &'a str {
+        self.title(graphic, default_input)
+            .and_then(|title| title.input.as_deref())
+            .unwrap_or(default_input)
+    }
+
+    /// False if the graphic is only read as a data source
+    pub fn push(&self, graphic: &str, default_input: &str) -> bool {
+        self.title(graphic, default_input)
+            .is_none_or(|title| title.push)
+    }
+
+    /// For graphics that have no default input
+    pub fn require_input(&self, graphic: &'static str) -> Result<(), Error> {
+        match self.0.get(graphic).and_then(|title| title.input.as_ref()) {
+            Some(_) => Ok(()),
+            None => Err(Error::TitleNotMapped(graphic)),
+        }
+    }
+
+    pub fn field(&self, graphic: &str, default_input: &str, name: String) -> String {
+        let Some(title) = self.title(graphic, default_input) else {
+            return name;
+        };
+        title
+            .fields
+            .iter()
+            .find_map(|(from, to)| Self::apply_template(from, to, &name))
+            .unwrap_or(name)
+    }
+
+    /// Renders `to` with the placeholders of `from`, if `name` matches `from`
+    fn apply_template(from: &str, to: &str, name: &str) -> Option<String> {
+        let mut values = HashMap::new();
+        let mut rest = name;
+        let mut template = from;
+        while !template.is_empty() {
+            if let Some(placeholder) = template.strip_prefix('{') {
+                let (key, after) = placeholder.split_once('}')?;
+                let digits = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                if digits == 0 {
+                    return None;
+                }
+                values.insert(key, &rest[..digits]);
+                rest = &rest[digits..];
+                template = after;
+            } else {
+                let literal = template.find('{').unwrap_or(template.len());
+                rest = rest.strip_prefix(&template[..literal])?;
+                template = &template[literal..];
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+        let mut rendered = to.to_string();
+        for (key, value) in values {
+            rendered = rendered.replace(&format!("{{{key}}}"), value);
+        }
+        Some(rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(json: &str) -> TitleMapping {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn template_renames_numbered_field() {
+        let mapping = mapping(
+            r#"{"VMixPlayerInfo": {"fields": {"p{player}s{hole}": "Player{player}Hole{hole}"}}}"#,
+        );
+        assert_eq!(
+            mapping.field("VMixPlayerInfo", "", "p2s14".to_string()),
+            "Player2Hole14"
+        );
+    }
+
+    #[test]
+    fn unmatched_field_keeps_its_name() {
+        let mapping = mapping(r#"{"VMixPlayerInfo": {"fields": {"p{player}s{hole}": "x"}}}"#);
+        assert_eq!(
+            mapping.field("VMixPlayerInfo", "", "p2s".to_string()),
+            "p2s"
+        );
+        assert_eq!(
+            mapping.field("VMixPlayerInfo", "", "p2name".to_string()),
+            "p2name"
+        );
+        assert_eq!(
+            mapping.field("CurrentPlayer", "", "p2s1".to_string()),
+            "p2s1"
+        );
+    }
+
+    #[test]
+    fn first_matching_template_wins() {
+        let mapping = mapping(
+            r#"{"VMixPlayerInfo": {"fields": {"p{player}s18": "Last", "p{player}s{hole}": "Hole{hole}", "p1s18": "Never"}}}"#,
+        );
+        assert_eq!(
+            mapping.field("VMixPlayerInfo", "", "p1s18".to_string()),
+            "Last"
+        );
+        assert_eq!(
+            mapping.field("VMixPlayerInfo", "", "p1s3".to_string()),
+            "Hole3"
+        );
+    }
+
+    #[test]
+    fn required_input_needs_an_input() {
+        let without = mapping(r#"{"RoundSummaryProperty": {"fields": {"title": "Title"}}}"#);
+        assert!(without.require_input("RoundSummaryProperty").is_err());
+        let with = mapping(r#"{"RoundSummaryProperty": {"input": "Round summary"}}"#);
+        assert!(with.require_input("RoundSummaryProperty").is_ok());
+    }
+
+    #[test]
+    fn input_falls_back_to_default_input() {
+        let mapping = mapping(r#"{"guid": {"input": "Scorecard"}}"#);
+        assert_eq!(mapping.input("VMixPlayerInfo", "guid"), "Scorecard");
+        assert_eq!(mapping.input("VMixPlayerInfo", "other"), "other");
+    }
+
+    #[test]
+    fn graphics_are_pushed_unless_turned_off() {
+        let mapping = mapping(r#"{"LeaderBoardProperty": {"push": false}, "CurrentPlayer": {}}"#);
+        assert!(!mapping.push("LeaderBoardProperty", ""));
+        assert!(mapping.push("CurrentPlayer", ""));
+        assert!(mapping.push("VMixPlayerInfo", ""));
+    }
+}